@@ -0,0 +1,272 @@
+use crate::encode::{Encode, Envelope};
+use crate::{Error, FullId, Meta, Version};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Cache of [`Meta`] of all installed apps. Stored in `sys/apps`.
+///
+/// Allows the launcher to show the list of apps without reading
+/// the `_meta` file of every app. Each entry has a fingerprint
+/// of the `_meta` file it was built from, so that stale entries can be detected.
+///
+/// The entries are sorted by the author ID and then the app ID.
+/// The order is restored when decoding, and duplicates are dropped,
+/// so a file written by another tool can't break lookups.
+/// Entries with invalid IDs fail decoding (see [`FullId`]).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(from = "Vec<AppEntry>")]
+pub struct AppIndex {
+    apps: Vec<AppEntry>,
+}
+
+impl AppIndex {
+    /// All entries, sorted by the author ID and then the app ID.
+    #[must_use]
+    pub fn apps(&self) -> &[AppEntry] {
+        &self.apps
+    }
+
+    /// Find the entry for the app with the given ID.
+    #[must_use]
+    pub fn get(&self, id: &FullId) -> Option<&AppEntry> {
+        let idx = self.find(id).ok()?;
+        self.apps.get(idx)
+    }
+
+    /// Add the entry or replace the existing one for the same app.
+    ///
+    /// Returns the replaced entry, if any.
+    pub fn insert(&mut self, entry: AppEntry) -> Option<AppEntry> {
+        match self.find(&entry.id) {
+            Ok(idx) => Some(core::mem::replace(&mut self.apps[idx], entry)),
+            Err(idx) => {
+                self.apps.insert(idx, entry);
+                None
+            }
+        }
+    }
+
+    /// Remove the entry for the app with the given ID.
+    pub fn remove(&mut self, id: &FullId) -> Option<AppEntry> {
+        let idx = self.find(id).ok()?;
+        Some(self.apps.remove(idx))
+    }
+
+    /// Keep only the entries for which the predicate returns true.
+    ///
+    /// Use it to drop entries for apps that aren't installed anymore.
+    pub fn retain(&mut self, f: impl FnMut(&AppEntry) -> bool) {
+        self.apps.retain(f);
+    }
+
+    /// Check if the entry for the app is missing or built from another `_meta` file.
+    ///
+    /// The fingerprint must be calculated with [`AppEntry::fingerprint`].
+    #[must_use]
+    pub fn is_stale(&self, id: &FullId, fingerprint: u32) -> bool {
+        self.get(id).is_none_or(|e| e.fingerprint != fingerprint)
+    }
+
+    fn find(&self, id: &FullId) -> Result<usize, usize> {
+        self.apps.binary_search_by(|e| e.id.cmp(id))
+    }
+}
+
+impl FromIterator<AppEntry> for AppIndex {
+    fn from_iter<T: IntoIterator<Item = AppEntry>>(iter: T) -> Self {
+        let mut index = Self::default();
+        for entry in iter {
+            index.insert(entry);
+        }
+        index
+    }
+}
+
+impl From<Vec<AppEntry>> for AppIndex {
+    fn from(value: Vec<AppEntry>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl Encode<'_> for AppIndex {}
+
+impl Envelope<'_> for AppIndex {
+    const FILE: &'static str = "sys/apps";
+    const MAGIC: [u8; 4] = *b"FFAI";
+    const VERSION: u8 = 1;
+}
+
+/// The cached subset of [`Meta`] of a single app.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct AppEntry {
+    /// The author ID and the app ID.
+    pub id: FullId,
+    pub author_name: String,
+    pub app_name: String,
+    /// Translated app names. See [`Meta::translations`].
+    pub names: Vec<([u8; 2], String)>,
+    pub launcher: bool,
+    pub version: u32,
+    pub min_players: u8,
+    pub max_players: u8,
+    pub min_runtime: Version,
+    /// The fingerprint of the `_meta` file the entry is built from.
+    pub fingerprint: u32,
+}
+
+impl AppEntry {
+    /// Build the entry from the app meta and the raw content of its `_meta` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] if the author ID or the app ID is not valid.
+    pub fn new(meta: &Meta<'_>, raw_meta: &[u8]) -> Result<Self, Error> {
+        let id = FullId::try_from(meta)?;
+        let names = meta
+            .translations
+            .iter()
+            .filter(|t| !t.name.is_empty())
+            .map(|t| (t.lang, t.name.to_string()))
+            .collect();
+        Ok(Self {
+            id,
+            author_name: meta.author_name.to_string(),
+            app_name: meta.app_name.to_string(),
+            names,
+            launcher: meta.launcher,
+            version: meta.version,
+            min_players: meta.min_players,
+            max_players: meta.max_players,
+            min_runtime: meta.min_runtime,
+            fingerprint: Self::fingerprint(raw_meta),
+        })
+    }
+
+    /// Calculate the fingerprint of the raw `_meta` file content.
+    ///
+    /// It's a 32-bit FNV-1a hash. Good enough to detect changes, not cryptographic.
+    #[must_use]
+    pub const fn fingerprint(raw_meta: &[u8]) -> u32 {
+        let mut hash: u32 = 0x811c_9dc5;
+        let mut i = 0;
+        while i < raw_meta.len() {
+            hash ^= raw_meta[i] as u32;
+            hash = hash.wrapping_mul(0x0100_0193);
+            i += 1;
+        }
+        hash
+    }
+
+    /// The app name in the given language.
+    ///
+    /// Falls back to [`AppEntry::app_name`] if there is no translation.
+    #[must_use]
+    pub fn name_for(&self, lang: [u8; 2]) -> &str {
+        match self.names.iter().find(|(l, _)| *l == lang) {
+            Some((_, name)) => name,
+            None => &self.app_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Translation;
+    use alloc::borrow::Cow;
+
+    fn key(entry: &AppEntry) -> (&str, &str) {
+        (entry.id.author_id(), entry.id.app_id())
+    }
+
+    fn entry(author_id: &str, app_id: &str) -> AppEntry {
+        let meta = Meta {
+            app_id,
+            app_name: "App",
+            author_id,
+            author_name: "Author",
+            ..Meta::default()
+        };
+        let raw = meta.encode_envelope_vec().unwrap();
+        AppEntry::new(&meta, &raw).unwrap()
+    }
+
+    #[test]
+    fn test_index() {
+        let mut index: AppIndex = [entry("b", "x"), entry("a", "y"), entry("a", "x")]
+            .into_iter()
+            .collect();
+        let keys: Vec<_> = index.apps().iter().map(key).collect();
+        assert_eq!(keys, [("a", "x"), ("a", "y"), ("b", "x")]);
+
+        let id = FullId::new("a", "y").unwrap();
+        let found = index.get(&id).unwrap();
+        assert_eq!(found.id.app_id(), "y");
+        assert!(!index.is_stale(&id, found.fingerprint));
+        assert!(index.is_stale(&id, found.fingerprint ^ 1));
+
+        let mut updated = entry("a", "y");
+        updated.version = 2;
+        let old = index.insert(updated).unwrap();
+        assert_eq!(old.version, 0);
+        assert_eq!(index.get(&id).unwrap().version, 2);
+        assert_eq!(index.apps().len(), 3);
+
+        assert!(index.remove(&id).is_some());
+        assert!(index.get(&id).is_none());
+        assert!(index.is_stale(&id, 0));
+        index.retain(|e| e.id.author_id() != "b");
+        assert_eq!(index.apps().len(), 1);
+
+        let raw = index.encode_envelope_vec().unwrap();
+        assert_eq!(AppIndex::decode_envelope(&raw).unwrap(), index);
+    }
+
+    #[test]
+    fn test_entry() {
+        let meta = Meta {
+            app_id: "snake",
+            author_id: "author",
+            app_name: "Snake",
+            translations: Cow::Owned(alloc::vec![Translation {
+                lang: *b"nl",
+                name: "Slang",
+                descr: "",
+            }]),
+            ..Meta::default()
+        };
+        let entry = AppEntry::new(&meta, b"raw").unwrap();
+        assert_eq!(entry.name_for(*b"nl"), "Slang");
+        assert_eq!(entry.name_for(*b"en"), "Snake");
+        assert_eq!(AppEntry::fingerprint(b""), 0x811c_9dc5);
+        assert_ne!(AppEntry::fingerprint(b"a"), AppEntry::fingerprint(b"b"));
+
+        let meta = Meta {
+            app_id: "../etc",
+            ..meta
+        };
+        assert!(AppEntry::new(&meta, b"raw").is_err());
+    }
+
+    #[test]
+    fn test_decode_unsorted() {
+        let mut dup = entry("a", "x");
+        dup.version = 2;
+        let raw = postcard::to_allocvec(&[entry("b", "x"), entry("a", "x"), dup][..]).unwrap();
+        let index = AppIndex::decode_compat(&raw).unwrap();
+        let keys: Vec<_> = index.apps().iter().map(key).collect();
+        assert_eq!(keys, [("a", "x"), ("b", "x")]);
+        let id = FullId::new("a", "x").unwrap();
+        assert_eq!(index.get(&id).unwrap().version, 2);
+    }
+
+    #[test]
+    fn test_decode_invalid_id() {
+        let mut raw = postcard::to_allocvec(&[entry("a", "x")][..]).unwrap();
+        // Replace "a" with "." in the author ID.
+        let pos = raw.iter().position(|b| *b == b'a').unwrap();
+        raw[pos] = b'.';
+        assert!(AppIndex::decode_compat(&raw).is_err());
+    }
+}
@@ -1,13 +1,21 @@
-use crate::encode::Encode;
+use crate::encode::{Encode, Envelope, EnvelopeError, from_bytes_exact};
+use crate::error::collect;
+use crate::{BadgeProgress, Error, Stats};
 use alloc::borrow::Cow;
+use alloc::vec::Vec;
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BadgeValidationError {
     EmptyName,
     NameTooLong,
     DescrTooLong,
     TooMuchXp,
+    HiddenGtSteps,
+    DuplicatePosition,
+    TotalXpTooHigh,
+    TooManyBadges,
 }
 
 impl BadgeValidationError {
@@ -18,8 +26,18 @@ impl BadgeValidationError {
             Self::NameTooLong => "name is too long",
             Self::DescrTooLong => "descr is too long",
             Self::TooMuchXp => "one badge cannot reward more than 200 XP",
+            Self::HiddenGtSteps => "hidden must be less than or equal to steps",
+            Self::DuplicatePosition => "another badge has the same position",
+            Self::TotalXpTooHigh => "all badges together cannot reward more than 1000 XP",
+            Self::TooManyBadges => "an app cannot have more than 256 badges",
         }
     }
+
+    /// Attach the index of the invalid badge to the error.
+    #[must_use]
+    pub const fn at(self, index: usize) -> Error {
+        Error::Badge { index, error: self }
+    }
 }
 
 impl Display for BadgeValidationError {
@@ -28,6 +46,8 @@ impl Display for BadgeValidationError {
     }
 }
 
+impl core::error::Error for BadgeValidationError {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Badges<'a> {
     /// Detailed information about achievements.
@@ -40,10 +60,68 @@ impl<'a> Badges<'a> {
     pub const fn new(badges: Cow<'a, [Badge<'a>]>) -> Self {
         Self { badges }
     }
+
+    /// Validate all badges, individually and as a collection.
+    ///
+    /// Unlike [`Badge::validate`], reports all problems at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Badge`] for every problem found,
+    /// with the index of the offending badge.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut push = |index, error: BadgeValidationError| errors.push(error.at(index));
+        let mut total_xp: u32 = 0;
+        for (index, badge) in self.badges.iter().enumerate() {
+            if let Err(error) = badge.validate() {
+                push(index, error);
+            }
+            let prev = &self.badges[..index];
+            if prev.iter().any(|b| b.position == badge.position) {
+                push(index, BadgeValidationError::DuplicatePosition);
+            }
+            let max_xp = u32::from(Stats::MAX_XP);
+            if total_xp <= max_xp {
+                total_xp += u32::from(badge.xp);
+                if total_xp > max_xp {
+                    push(index, BadgeValidationError::TotalXpTooHigh);
+                }
+            }
+        }
+        // Badges are referred to by u8 index, see crate::manuals::Page::badge.
+        if self.badges.len() > 256 {
+            push(256, BadgeValidationError::TooManyBadges);
+        }
+        collect(errors)
+    }
 }
 
 impl<'a> Encode<'a> for Badges<'a> {}
 
+impl<'a> Envelope<'a> for Badges<'a> {
+    const FILE: &'static str = "_badges";
+    const MAGIC: [u8; 4] = *b"FFBG";
+    const VERSION: u8 = 2;
+
+    fn decode_version(version: u8, payload: &'a [u8]) -> Result<Self, EnvelopeError> {
+        if version == 0
+            && let Ok(badges) = from_bytes_exact(payload)
+        {
+            return Ok(badges);
+        }
+        match version {
+            0 | 1 => {
+                let badges: Vec<BadgeV1<'a>> = from_bytes_exact(payload)?;
+                let badges = badges.into_iter().map(Badge::from).collect();
+                Ok(Self::new(Cow::Owned(badges)))
+            }
+            Self::VERSION => Ok(postcard::from_bytes(payload)?),
+            _ => Err(EnvelopeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Badge<'a> {
     /// The order in which badges should be displayed, ascending.
@@ -54,6 +132,13 @@ pub struct Badge<'a> {
     /// How much XP earning the badge brings to the player.
     pub xp: u8,
 
+    /// The number of steps required to earn the badge.
+    ///
+    /// Used as [`BadgeProgress::goal`][crate::BadgeProgress::goal].
+    /// Zero means unknown. That's the case for badges from files
+    /// written before the field was added. Then the goal stored in stats is kept.
+    pub steps: u16,
+
     /// The number of steps required for the badge to be shown.
     ///
     /// If zero, the badge is always shown. If equal to the number of steps
@@ -68,6 +153,14 @@ pub struct Badge<'a> {
 }
 
 impl Badge<'_> {
+    /// If the badge should be shown to the player with the given progress.
+    ///
+    /// Applies the [`Badge::hidden`] threshold. Earned badges are always shown.
+    #[must_use]
+    pub const fn is_visible(&self, progress: &BadgeProgress) -> bool {
+        progress.done >= self.hidden || progress.earned()
+    }
+
     /// Validate badge attributes.
     ///
     /// # Errors
@@ -86,6 +179,143 @@ impl Badge<'_> {
         if self.xp > 200 {
             return Err(BadgeValidationError::TooMuchXp);
         }
+        if self.steps != 0 && self.hidden > self.steps {
+            return Err(BadgeValidationError::HiddenGtSteps);
+        }
         Ok(())
     }
 }
+
+/// The layout of [`Badge`] before [`Badge::steps`] was added.
+#[derive(Deserialize)]
+struct BadgeV1<'a> {
+    position: u16,
+    xp: u8,
+    hidden: u16,
+    name: &'a str,
+    descr: &'a str,
+}
+
+impl<'a> From<BadgeV1<'a>> for Badge<'a> {
+    fn from(v: BadgeV1<'a>) -> Self {
+        Self {
+            position: v.position,
+            xp: v.xp,
+            steps: 0,
+            hidden: v.hidden,
+            name: v.name,
+            descr: v.descr,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct BadgeV1Raw<'a> {
+        position: u16,
+        xp: u8,
+        hidden: u16,
+        name: &'a str,
+        descr: &'a str,
+    }
+
+    #[test]
+    fn test_decode_v1() {
+        let old = [BadgeV1Raw {
+            position: 3,
+            xp: 20,
+            hidden: 0,
+            name: "Winner",
+            descr: "Win the game",
+        }];
+        let raw = postcard::to_allocvec(&old.as_slice()).unwrap();
+        let badges = Badges::decode_compat(&raw).unwrap();
+        assert_eq!(badges.badges.len(), 1);
+        let badge = &badges.badges[0];
+        assert_eq!(badge.position, 3);
+        assert_eq!(badge.xp, 20);
+        assert_eq!(badge.steps, 0);
+        assert_eq!(badge.name, "Winner");
+        assert_eq!(badge.descr, "Win the game");
+    }
+
+    #[test]
+    fn test_decode_bare_current() {
+        let given = Badges::new(Cow::Owned(alloc::vec![Badge {
+            position: 1,
+            xp: 10,
+            steps: 5,
+            hidden: 2,
+            name: "Collector",
+            descr: "Collect 5 coins",
+        }]));
+        let raw = given.encode_vec().unwrap();
+        assert_eq!(Badges::decode_compat(&raw).unwrap(), given);
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let given = Badges::new(Cow::Owned(alloc::vec![Badge {
+            position: 1,
+            xp: 10,
+            steps: 5,
+            hidden: 2,
+            name: "Collector",
+            descr: "Collect 5 coins",
+        }]));
+        let raw = given.encode_envelope_vec().unwrap();
+        let actual = Badges::decode_envelope(&raw).unwrap();
+        assert_eq!(given, actual);
+    }
+
+    #[test]
+    fn test_validate_collection() {
+        let badge = |position, xp| Badge {
+            position,
+            xp,
+            steps: 1,
+            hidden: 0,
+            name: "badge",
+            descr: "",
+        };
+        let badges = Badges::new(Cow::Owned(alloc::vec![badge(1, 100), badge(2, 100)]));
+        assert!(badges.validate().is_ok());
+
+        let mut list = alloc::vec![badge(0, 200); 6];
+        list[2] = badge(2, 200);
+        list[3] = badge(3, 201);
+        list[4] = badge(4, 200);
+        list[5] = badge(5, 200);
+        let errors = Badges::new(Cow::Owned(list)).validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                Error::Badge {
+                    index: 1,
+                    error: BadgeValidationError::DuplicatePosition
+                },
+                Error::Badge {
+                    index: 3,
+                    error: BadgeValidationError::TooMuchXp
+                },
+                Error::Badge {
+                    index: 4,
+                    error: BadgeValidationError::TotalXpTooHigh
+                },
+            ]
+        );
+
+        let list: Vec<_> = (0..300).map(|i| badge(i, 0)).collect();
+        let errors = Badges::new(Cow::Owned(list)).validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [Error::Badge {
+                index: 256,
+                error: BadgeValidationError::TooManyBadges
+            }]
+        );
+    }
+}
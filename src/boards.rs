@@ -1,12 +1,18 @@
-use crate::encode::Encode;
+use crate::Error;
+use crate::encode::{Encode, Envelope, EnvelopeError, from_bytes_exact};
+use crate::error::collect;
 use alloc::borrow::Cow;
+use alloc::vec::Vec;
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoardValidationError {
     EmptyName,
     NameTooLong,
     MinGtMax,
+    DuplicatePosition,
+    TooManyBoards,
 }
 
 impl BoardValidationError {
@@ -16,8 +22,16 @@ impl BoardValidationError {
             Self::EmptyName => "name must not be empty",
             Self::NameTooLong => "name is too long",
             Self::MinGtMax => "min must be less than or equal to max",
+            Self::DuplicatePosition => "another board has the same position",
+            Self::TooManyBoards => "an app cannot have more than 256 boards",
         }
     }
+
+    /// Attach the index of the invalid board to the error.
+    #[must_use]
+    pub const fn at(self, index: usize) -> Error {
+        Error::Board { index, error: self }
+    }
 }
 
 impl Display for BoardValidationError {
@@ -26,6 +40,34 @@ impl Display for BoardValidationError {
     }
 }
 
+impl core::error::Error for BoardValidationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreError {
+    BelowMin,
+    AboveMax,
+    InvalidFormat,
+}
+
+impl ScoreError {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::BelowMin => "score is below the board minimum",
+            Self::AboveMax => "score is above the board maximum",
+            Self::InvalidFormat => "score has invalid format",
+        }
+    }
+}
+
+impl Display for ScoreError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl core::error::Error for ScoreError {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Boards<'a> {
     #[serde(borrow)]
@@ -37,10 +79,66 @@ impl<'a> Boards<'a> {
     pub const fn new(boards: Cow<'a, [Board<'a>]>) -> Self {
         Self { boards }
     }
+
+    /// Validate all boards, individually and as a collection.
+    ///
+    /// Unlike [`Board::validate`], reports all problems at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Board`] for every problem found,
+    /// with the index of the offending board.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut push = |index, error: BoardValidationError| errors.push(error.at(index));
+        for (index, board) in self.boards.iter().enumerate() {
+            if let Err(error) = board.validate() {
+                push(index, error);
+            }
+            let prev = &self.boards[..index];
+            if prev.iter().any(|b| b.position == board.position) {
+                push(index, BoardValidationError::DuplicatePosition);
+            }
+        }
+        // Boards are referred to by u8 index, see crate::manuals::Page::score.
+        if self.boards.len() > 256 {
+            push(256, BoardValidationError::TooManyBoards);
+        }
+        collect(errors)
+    }
 }
 
 impl<'a> Encode<'a> for Boards<'a> {}
 
+impl<'a> Envelope<'a> for Boards<'a> {
+    const FILE: &'static str = "_boards";
+    const MAGIC: [u8; 4] = *b"FFBD";
+    const VERSION: u8 = 2;
+
+    fn decode_version(version: u8, payload: &'a [u8]) -> Result<Self, EnvelopeError> {
+        match version {
+            // Version 1 had 16-bit scores. They are encoded as varints,
+            // so the layout is compatible but the default limits need to be widened.
+            // A bare file may also be in the current layout. Then the widening
+            // affects only boards with limits of exactly `i16::MIN`/`i16::MAX`.
+            0 | 1 => {
+                let mut boards: Self = from_bytes_exact(payload)?;
+                for board in boards.boards.to_mut() {
+                    if board.min == i32::from(i16::MIN) {
+                        board.min = i32::MIN;
+                    }
+                    if board.max == i32::from(i16::MAX) {
+                        board.max = i32::MAX;
+                    }
+                }
+                Ok(boards)
+            }
+            Self::VERSION => Ok(postcard::from_bytes(payload)?),
+            _ => Err(EnvelopeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct Board<'a> {
     /// The order in which the board should be displayed, ascending.
@@ -50,18 +148,27 @@ pub struct Board<'a> {
     ///
     /// It's possible to have negative scores when the app needs scores on the board
     /// to be ordered in ascending order rather than descending.
-    /// So the default minimum is [`i16::MIN`] rather than 0.
-    pub min: i16,
+    /// So the default minimum is [`i32::MIN`] rather than 0.
+    ///
+    /// The value [`i32::MIN`] itself is never accepted as a score
+    /// because it marks empty slots (see [`BoardScores::EMPTY`][crate::BoardScores::EMPTY]).
+    pub min: i32,
 
     /// The maximum value for a score to be added to the board.
     ///
     /// Useful for filtering out obvious cheating.
-    pub max: i16,
+    /// So the default maximum is [`i32::MAX`].
+    pub max: i32,
 
     /// If the score should be formatted as time.
+    ///
+    /// The score is the number of seconds (multiplied by 10 to the power
+    /// of [`Board::decimals`], if not zero).
     pub time: bool,
 
     /// Digits after decimal point.
+    ///
+    /// For example, the score `1234` with 2 decimals is formatted as `12.34`.
     pub decimals: u8,
 
     /// Human-readable board name.
@@ -86,4 +193,349 @@ impl Board<'_> {
         }
         Ok(())
     }
+
+    /// Check if the score is in the board's `min..=max` range.
+    ///
+    /// [`i32::MIN`] is reserved for empty slots and is always rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScoreError`] if the score is too low or too high.
+    pub const fn check_score(&self, score: i32) -> Result<(), ScoreError> {
+        if score < self.min || score == i32::MIN {
+            return Err(ScoreError::BelowMin);
+        }
+        if score > self.max {
+            return Err(ScoreError::AboveMax);
+        }
+        Ok(())
+    }
+
+    /// Get a [`Display`] adapter formatting the score according to the board settings.
+    ///
+    /// See [`Board::format_score`].
+    #[must_use]
+    pub const fn display_score(&self, score: i32) -> ScoreDisplay {
+        ScoreDisplay {
+            score,
+            time: self.time,
+            decimals: self.decimals,
+        }
+    }
+
+    /// Format the score according to [`Board::time`] and [`Board::decimals`].
+    ///
+    /// Regular scores are formatted as fixed-point numbers (`12.34`),
+    /// time as `mm:ss` or `h:mm:ss` (followed by the fraction of a second, if any).
+    /// Time is never negative, so negative time scores (used to sort the board
+    /// in ascending order) are formatted as their absolute value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer is too small. 24 bytes is always enough
+    /// unless [`Board::decimals`] is absurdly high.
+    pub fn format_score<'b>(
+        &self,
+        score: i32,
+        buf: &'b mut [u8],
+    ) -> Result<&'b str, core::fmt::Error> {
+        use core::fmt::Write;
+        let mut writer = BufWriter { buf, len: 0 };
+        write!(writer, "{}", self.display_score(score))?;
+        let BufWriter { buf, len } = writer;
+        core::str::from_utf8(&buf[..len]).map_err(|_| core::fmt::Error)
+    }
+
+    /// Parse the score formatted by [`Board::format_score`].
+    ///
+    /// Since the time is always formatted as a positive value,
+    /// the parsed time is negated if the board doesn't allow positive scores.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScoreError`] if the text is not a valid score
+    /// or the score is not in the board's range.
+    pub fn parse_score(&self, s: &str) -> Result<i32, ScoreError> {
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(s) if !self.time => (true, s),
+            _ => (false, s),
+        };
+        let (main, frac) = match s.split_once('.') {
+            Some((_, "")) => return Err(ScoreError::InvalidFormat),
+            Some((main, frac)) => (main, frac),
+            None => (s, ""),
+        };
+        if frac.len() > usize::from(self.decimals) {
+            return Err(ScoreError::InvalidFormat);
+        }
+        let main = if self.time {
+            parse_time(main)?
+        } else {
+            parse_digits(main)?
+        };
+        let mut frac_val = if frac.is_empty() {
+            0
+        } else {
+            parse_digits(frac)?
+        };
+        for _ in frac.len()..usize::from(self.decimals) {
+            frac_val = frac_val.checked_mul(10).ok_or(ScoreError::AboveMax)?;
+        }
+        let mut val = main;
+        for _ in 0..self.decimals {
+            val = val.checked_mul(10).ok_or(ScoreError::AboveMax)?;
+        }
+        let mut val = val.checked_add(frac_val).ok_or(ScoreError::AboveMax)?;
+        if neg || self.time && self.max <= 0 {
+            val = -val;
+        }
+        let score = match i32::try_from(val) {
+            Ok(score) => score,
+            Err(_) if val < 0 => return Err(ScoreError::BelowMin),
+            Err(_) => return Err(ScoreError::AboveMax),
+        };
+        self.check_score(score)?;
+        Ok(score)
+    }
+}
+
+/// Score formatted according to the board settings.
+///
+/// Created by [`Board::display_score`].
+pub struct ScoreDisplay {
+    score: i32,
+    time: bool,
+    decimals: u8,
+}
+
+impl Display for ScoreDisplay {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let abs = u64::from(self.score.unsigned_abs());
+        let unit = 10_u64.checked_pow(u32::from(self.decimals));
+        let (main, frac) = unit.map_or((0, abs), |unit| (abs / unit, abs % unit));
+        if self.time {
+            let hours = main / 3600;
+            let minutes = main / 60 % 60;
+            let seconds = main % 60;
+            if hours != 0 {
+                write!(f, "{hours}:{minutes:02}:{seconds:02}")?;
+            } else {
+                write!(f, "{minutes:02}:{seconds:02}")?;
+            }
+        } else {
+            if self.score < 0 {
+                write!(f, "-")?;
+            }
+            write!(f, "{main}")?;
+        }
+        if self.decimals != 0 {
+            let width = usize::from(self.decimals);
+            write!(f, ".{frac:0width$}")?;
+        }
+        Ok(())
+    }
+}
+
+/// [`core::fmt::Write`] implementation writing into a fixed-size buffer.
+struct BufWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl core::fmt::Write for BufWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        let Some(dst) = self.buf.get_mut(self.len..end) else {
+            return Err(core::fmt::Error);
+        };
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Parse time in `m:ss` or `h:mm:ss` format into seconds.
+fn parse_time(s: &str) -> Result<i64, ScoreError> {
+    let mut parts = s.rsplit(':');
+    let seconds = parts.next().ok_or(ScoreError::InvalidFormat)?;
+    let minutes = parts.next().ok_or(ScoreError::InvalidFormat)?;
+    let hours = parts.next();
+    if parts.next().is_some() || seconds.len() != 2 {
+        return Err(ScoreError::InvalidFormat);
+    }
+    let seconds = parse_digits(seconds)?;
+    let minutes = parse_digits(minutes)?;
+    if seconds >= 60 {
+        return Err(ScoreError::InvalidFormat);
+    }
+    let hours = match hours {
+        Some(hours) => {
+            if minutes >= 60 {
+                return Err(ScoreError::InvalidFormat);
+            }
+            parse_digits(hours)?
+        }
+        None => 0,
+    };
+    let total = hours
+        .checked_mul(60)
+        .and_then(|t| t.checked_add(minutes))
+        .and_then(|t| t.checked_mul(60))
+        .and_then(|t| t.checked_add(seconds));
+    total.ok_or(ScoreError::AboveMax)
+}
+
+/// Parse a non-empty sequence of ASCII digits.
+fn parse_digits(s: &str) -> Result<i64, ScoreError> {
+    if s.is_empty() {
+        return Err(ScoreError::InvalidFormat);
+    }
+    let mut res: i64 = 0;
+    for c in s.bytes() {
+        if !c.is_ascii_digit() {
+            return Err(ScoreError::InvalidFormat);
+        }
+        res = res
+            .checked_mul(10)
+            .and_then(|r| r.checked_add(i64::from(c - b'0')))
+            .ok_or(ScoreError::AboveMax)?;
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(time: bool, decimals: u8) -> Board<'static> {
+        Board {
+            position: 0,
+            min: i32::MIN,
+            max: i32::MAX,
+            time,
+            decimals,
+            name: "board",
+        }
+    }
+
+    fn format(board: &Board, score: i32) -> alloc::string::String {
+        let mut buf = [0; 24];
+        let res = board.format_score(score, &mut buf).unwrap();
+        assert_eq!(res, board.display_score(score).to_string());
+        res.into()
+    }
+
+    #[test]
+    fn test_format_score() {
+        assert_eq!(format(&board(false, 0), 1234), "1234");
+        assert_eq!(format(&board(false, 0), -1234), "-1234");
+        assert_eq!(format(&board(false, 2), 1234), "12.34");
+        assert_eq!(format(&board(false, 2), -1234), "-12.34");
+        assert_eq!(format(&board(false, 2), 5), "0.05");
+        assert_eq!(format(&board(false, 2), -5), "-0.05");
+        assert_eq!(format(&board(false, 7), 12), "0.0000012");
+        assert_eq!(format(&board(false, 21), 1), "0.000000000000000000001");
+        assert_eq!(format(&board(true, 0), 75), "01:15");
+        assert_eq!(format(&board(true, 0), -75), "01:15");
+        assert_eq!(format(&board(true, 0), 3675), "1:01:15");
+        assert_eq!(format(&board(true, 1), 755), "01:15.5");
+        assert_eq!(format(&board(true, 2), 7505), "01:15.05");
+    }
+
+    #[test]
+    fn test_format_score_small_buf() {
+        let mut buf = [0; 4];
+        assert!(board(false, 2).format_score(12345, &mut buf).is_err());
+    }
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(board(false, 0).parse_score("1234"), Ok(1234));
+        assert_eq!(board(false, 0).parse_score("-1234"), Ok(-1234));
+        assert_eq!(board(false, 2).parse_score("12.34"), Ok(1234));
+        assert_eq!(board(false, 2).parse_score("12.3"), Ok(1230));
+        assert_eq!(board(false, 2).parse_score("12"), Ok(1200));
+        assert_eq!(board(false, 2).parse_score("-0.05"), Ok(-5));
+        assert_eq!(board(true, 0).parse_score("01:15"), Ok(75));
+        assert_eq!(board(true, 0).parse_score("1:01:15"), Ok(3675));
+        assert_eq!(board(true, 2).parse_score("01:15.05"), Ok(7505));
+        let mut asc = board(true, 0);
+        asc.max = 0;
+        assert_eq!(asc.parse_score("01:15"), Ok(-75));
+
+        let err = Err(ScoreError::InvalidFormat);
+        assert_eq!(board(false, 2).parse_score("12.345"), err);
+        assert_eq!(board(false, 2).parse_score("12."), err);
+        assert_eq!(board(false, 2).parse_score(".5"), err);
+        assert_eq!(board(false, 0).parse_score("1e3"), err);
+        assert_eq!(board(false, 0).parse_score(""), err);
+        assert_eq!(board(true, 0).parse_score("75"), err);
+        assert_eq!(board(true, 0).parse_score("1:75"), err);
+        assert_eq!(board(true, 0).parse_score("1:61:15"), err);
+        assert_eq!(board(true, 0).parse_score("-01:15"), err);
+        assert_eq!(
+            board(false, 0).parse_score("3000000000"),
+            Err(ScoreError::AboveMax)
+        );
+        assert_eq!(
+            board(false, 0).parse_score("-3000000000"),
+            Err(ScoreError::BelowMin)
+        );
+    }
+
+    #[test]
+    fn test_score_roundtrip() {
+        for b in [
+            board(false, 0),
+            board(false, 3),
+            board(true, 0),
+            board(true, 2),
+        ] {
+            for score in [0, 1, 59, 60, 99, 1234, 3600, i32::MAX] {
+                assert_eq!(b.parse_score(&format(&b, score)), Ok(score));
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_v1_limits() {
+        let old = [
+            (0u16, i16::MIN, i16::MAX, false, 0u8, "a"),
+            (1, -100, 100, false, 0, "b"),
+        ];
+        let mut raw = postcard::to_allocvec(&&old[..]).unwrap();
+        raw.splice(0..0, *b"FFBD\x01");
+        let boards = Boards::decode_envelope(&raw).unwrap();
+        assert_eq!(boards.boards[0].min, i32::MIN);
+        assert_eq!(boards.boards[0].max, i32::MAX);
+        assert_eq!(boards.boards[1].min, -100);
+        assert_eq!(boards.boards[1].max, 100);
+    }
+
+    #[test]
+    fn test_validate_collection() {
+        let mut list = alloc::vec![board(false, 0); 3];
+        list[1].position = 1;
+        list[1].min = 10;
+        list[1].max = 0;
+        list[2].name = "";
+        let errors = Boards::new(Cow::Owned(list)).validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                Error::Board {
+                    index: 1,
+                    error: BoardValidationError::MinGtMax
+                },
+                Error::Board {
+                    index: 2,
+                    error: BoardValidationError::EmptyName
+                },
+                Error::Board {
+                    index: 2,
+                    error: BoardValidationError::DuplicatePosition
+                },
+            ]
+        );
+    }
 }
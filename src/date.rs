@@ -0,0 +1,290 @@
+use crate::Error;
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// The number of days between 0000-03-01 and 1970-01-01.
+const EPOCH_SHIFT: i32 = 719_468;
+
+/// The number of days in a 400 years cycle.
+const DAYS_IN_ERA: i32 = 146_097;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateError {
+    InvalidMonth,
+    InvalidDay,
+    InvalidFormat,
+    OutOfRange,
+}
+
+impl DateError {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidMonth => "month must be from 1 to 12",
+            Self::InvalidDay => "day is out of range for the month",
+            Self::InvalidFormat => "date must be in YYYY-MM-DD format",
+            Self::OutOfRange => "year is out of range",
+        }
+    }
+
+    /// Attach the name of the field with the invalid date to the error.
+    ///
+    /// Useful for `?`: `date.validate().map_err(|e| e.for_field("installed_on"))?`.
+    #[must_use]
+    pub const fn for_field(self, field: &'static str) -> Error {
+        Error::DateField { field, error: self }
+    }
+}
+
+impl Display for DateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl core::error::Error for DateError {}
+
+/// A calendar date in the proleptic Gregorian calendar.
+///
+/// Encoded as a tuple of year, month, and day of month.
+///
+/// Decoding doesn't validate the date, so that a single bad date
+/// (like `0000-00-00` used as a placeholder) doesn't make the whole file unreadable.
+/// Use [`Date::validate`] to check decoded dates. Day arithmetic on invalid dates
+/// doesn't panic but the result is meaningless.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(from = "(u16, u8, u8)", into = "(u16, u8, u8)")]
+pub struct Date {
+    pub year: u16,
+    /// Month from 1 (January) to 12 (December).
+    pub month: u8,
+    /// Day of month, starting from 1.
+    pub day: u8,
+}
+
+impl Date {
+    /// Create a new date.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DateError`] if the month or the day is out of range.
+    pub const fn new(year: u16, month: u8, day: u8) -> Result<Self, DateError> {
+        let date = Self { year, month, day };
+        match date.validate() {
+            Ok(()) => Ok(date),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check that the date exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DateError`] if the month or the day is out of range.
+    pub const fn validate(&self) -> Result<(), DateError> {
+        if self.month < 1 || self.month > 12 {
+            return Err(DateError::InvalidMonth);
+        }
+        if self.day < 1 || self.day > days_in_month(self.year, self.month) {
+            return Err(DateError::InvalidDay);
+        }
+        Ok(())
+    }
+
+    /// The number of days since 1970-01-01.
+    ///
+    /// Negative for earlier dates.
+    #[must_use]
+    pub const fn to_days(self) -> i32 {
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let month = self.month as i32;
+        let day = self.day as i32;
+        let year = self.year as i32 - if month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * DAYS_IN_ERA + doe - EPOCH_SHIFT
+    }
+
+    /// Construct the date from the number of days since 1970-01-01.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DateError::OutOfRange`] if the year doesn't fit into `u16`.
+    pub fn from_days(days: i32) -> Result<Self, DateError> {
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days.checked_add(EPOCH_SHIFT).ok_or(DateError::OutOfRange)?;
+        let era = days.div_euclid(DAYS_IN_ERA);
+        let doe = days - era * DAYS_IN_ERA;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i32::from(month <= 2);
+        let Ok(year) = u16::try_from(year) else {
+            return Err(DateError::OutOfRange);
+        };
+        // Month and day are always in range for the calculated values.
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let date = Self {
+            year,
+            month: month as u8,
+            day: day as u8,
+        };
+        Ok(date)
+    }
+
+    /// The date that is the given number of days later (or earlier, if negative).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DateError::OutOfRange`] if the resulting year doesn't fit into `u16`.
+    pub fn add_days(self, days: i32) -> Result<Self, DateError> {
+        let days = self
+            .to_days()
+            .checked_add(days)
+            .ok_or(DateError::OutOfRange)?;
+        Self::from_days(days)
+    }
+
+    /// The number of days passed from the given date to this one.
+    ///
+    /// Negative if the given date is later than this one.
+    #[must_use]
+    pub const fn days_since(self, earlier: Self) -> i32 {
+        self.to_days() - earlier.to_days()
+    }
+}
+
+/// Converts the tuple into a date without validation.
+impl From<(u16, u8, u8)> for Date {
+    fn from((year, month, day): (u16, u8, u8)) -> Self {
+        Self { year, month, day }
+    }
+}
+
+impl From<Date> for (u16, u8, u8) {
+    fn from(value: Date) -> Self {
+        (value.year, value.month, value.day)
+    }
+}
+
+/// Formats the date as ISO 8601 (`YYYY-MM-DD`).
+impl Display for Date {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Parses the date in ISO 8601 format (`YYYY-MM-DD`).
+impl FromStr for Date {
+    type Err = DateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let b = s.as_bytes();
+        if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+            return Err(DateError::InvalidFormat);
+        }
+        let year = parse_digits(&b[..4])?;
+        let month = parse_digits(&b[5..7])?;
+        let day = parse_digits(&b[8..])?;
+        let Ok(month) = u8::try_from(month) else {
+            return Err(DateError::InvalidMonth);
+        };
+        let Ok(day) = u8::try_from(day) else {
+            return Err(DateError::InvalidDay);
+        };
+        Self::new(year, month, day)
+    }
+}
+
+fn parse_digits(b: &[u8]) -> Result<u16, DateError> {
+    let mut res: u16 = 0;
+    for &c in b {
+        if !c.is_ascii_digit() {
+            return Err(DateError::InvalidFormat);
+        }
+        res = res * 10 + u16::from(c - b'0');
+    }
+    Ok(res)
+}
+
+const fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert_eq!(Date::new(2023, 2, 29), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(1900, 2, 29), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2024, 4, 31), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2024, 1, 0), Err(DateError::InvalidDay));
+        assert_eq!(Date::new(2024, 13, 1), Err(DateError::InvalidMonth));
+        assert_eq!(Date::new(2024, 0, 1), Err(DateError::InvalidMonth));
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(Date::new(1970, 1, 1).unwrap().to_days(), 0);
+        assert_eq!(Date::new(1969, 12, 31).unwrap().to_days(), -1);
+        assert_eq!(Date::new(2000, 3, 1).unwrap().to_days(), 11_017);
+        for days in [-719_468, -1, 0, 1, 11_017, 19_782, 2_932_896] {
+            assert_eq!(Date::from_days(days).unwrap().to_days(), days);
+        }
+        let a = Date::new(2023, 12, 31).unwrap();
+        let b = Date::new(2024, 3, 1).unwrap();
+        assert_eq!(b.days_since(a), 61);
+        assert_eq!(a.days_since(b), -61);
+        assert_eq!(a.add_days(61), Ok(b));
+        assert_eq!(b.add_days(-61), Ok(a));
+        assert!(a < b);
+        let max = Date::new(u16::MAX, 12, 31).unwrap();
+        assert_eq!(max.add_days(1), Err(DateError::OutOfRange));
+    }
+
+    #[test]
+    fn test_iso() {
+        let date = Date::new(2024, 2, 9).unwrap();
+        assert_eq!(date.to_string(), "2024-02-09");
+        assert_eq!("2024-02-09".parse(), Ok(date));
+        assert_eq!(
+            "0999-12-31".parse::<Date>().unwrap().to_string(),
+            "0999-12-31"
+        );
+        assert_eq!("2024-2-09".parse::<Date>(), Err(DateError::InvalidFormat));
+        assert_eq!("2024-0a-09".parse::<Date>(), Err(DateError::InvalidFormat));
+        assert_eq!("2024-02-30".parse::<Date>(), Err(DateError::InvalidDay));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let raw = postcard::to_allocvec(&(2024u16, 13u8, 1u8)).unwrap();
+        let date: Date = postcard::from_bytes(&raw).unwrap();
+        assert_eq!(date.validate(), Err(DateError::InvalidMonth));
+        let zero = Date::from((0, 0, 0));
+        assert_eq!(zero.validate(), Err(DateError::InvalidMonth));
+        let _ = zero.days_since(Date::new(2024, 12, 1).unwrap());
+        let raw = postcard::to_allocvec(&(2024u16, 12u8, 1u8)).unwrap();
+        let date: Date = postcard::from_bytes(&raw).unwrap();
+        assert_eq!(date, Date::new(2024, 12, 1).unwrap());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::Error;
 use alloc::vec::Vec;
 
 pub trait Encode<'a>
@@ -6,6 +7,10 @@ where
 {
     /// Load object from bytes generated by [`Encode::encode`].
     ///
+    /// The error has no context about the file being decoded. For files,
+    /// prefer [`Envelope::decode_compat`] which returns [`Error`] with the file name,
+    /// or attach it with [`EnvelopeError::for_file`].
+    ///
     /// # Errors
     ///
     /// May return an error if the buffer does not contain a valid object.
@@ -46,3 +51,198 @@ where
         postcard::serialize_with_flavor(self, flavor).unwrap()
     }
 }
+
+/// The size of the envelope header: the magic bytes followed by the schema version.
+pub const ENVELOPE_HEADER_SIZE: usize = 5;
+
+/// Error returned when decoding a file wrapped in an [`Envelope`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The file is shorter than the envelope header.
+    TooShort,
+    /// The magic bytes don't match the expected file kind.
+    WrongKind { expected: [u8; 4], actual: [u8; 4] },
+    /// The schema version is not supported by this version of the crate.
+    UnsupportedVersion(u8),
+    /// The envelope header is valid but the payload cannot be decoded.
+    Postcard(postcard::Error),
+}
+
+impl From<postcard::Error> for EnvelopeError {
+    fn from(value: postcard::Error) -> Self {
+        Self::Postcard(value)
+    }
+}
+
+impl EnvelopeError {
+    /// Attach the name of the file that failed to decode to the error.
+    ///
+    /// Useful for `?` with [`Encode::decode`]:
+    /// `Stats::decode(raw).map_err(|e| EnvelopeError::from(e).for_file("stats"))?`.
+    #[must_use]
+    pub const fn for_file(self, file: &'static str) -> Error {
+        Error::Envelope { file, error: self }
+    }
+}
+
+impl core::fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "file is too short"),
+            Self::WrongKind { expected, actual } => {
+                let expected = core::str::from_utf8(expected).unwrap_or("????");
+                match core::str::from_utf8(actual) {
+                    Ok(actual) => write!(f, "expected {expected} file, got {actual}"),
+                    Err(_) => write!(f, "expected {expected} file, got unknown file kind"),
+                }
+            }
+            Self::UnsupportedVersion(v) => write!(f, "unsupported schema version: {v}"),
+            Self::Postcard(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Postcard(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Encoding with a header that identifies the file kind and the schema version.
+///
+/// The header is [`Envelope::MAGIC`] followed by a single byte of [`Envelope::VERSION`].
+/// The rest of the file is the object encoded the same way as by [`Encode`].
+pub trait Envelope<'a>: Encode<'a> {
+    /// The file name (or path) in vFS where the object is stored.
+    ///
+    /// Used only to provide a context for errors.
+    const FILE: &'static str;
+
+    /// The magic bytes identifying the file kind.
+    const MAGIC: [u8; 4];
+
+    /// The current schema version. Incremented every time the layout changes.
+    const VERSION: u8;
+
+    /// Decode the payload of the given schema version.
+    ///
+    /// The version 0 means a bare file without the envelope header.
+    /// It's ambiguous: the file may be written by older firmware in an old layout
+    /// or by [`Encode::encode_vec`] in the current layout. So the version 0
+    /// must be decoded strictly (rejecting trailing bytes), trying the current
+    /// layout first and then the older layouts, from the newest to the oldest.
+    ///
+    /// By default, only the current layout is supported.
+    /// Types that changed their layout should override it
+    /// to support decoding older versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EnvelopeError::UnsupportedVersion`] if the version is not known
+    /// and [`EnvelopeError::Postcard`] if the payload is not valid.
+    fn decode_version(version: u8, payload: &'a [u8]) -> Result<Self, EnvelopeError> {
+        if version == 0 {
+            return Ok(from_bytes_exact(payload)?);
+        }
+        if version != Self::VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(version));
+        }
+        Ok(postcard::from_bytes(payload)?)
+    }
+
+    /// Load object from bytes generated by [`Envelope::encode_envelope_vec`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Envelope`] if the header is missing, the file is of another kind,
+    /// the schema version is not supported, or the payload is not valid.
+    fn decode_envelope(s: &'a [u8]) -> Result<Self, Error> {
+        let Some((header, payload)) = s.split_at_checked(ENVELOPE_HEADER_SIZE) else {
+            return Err(envelope_error::<Self>(EnvelopeError::TooShort));
+        };
+        let mut actual = [0; 4];
+        actual.copy_from_slice(&header[..4]);
+        if actual != Self::MAGIC {
+            return Err(envelope_error::<Self>(EnvelopeError::WrongKind {
+                expected: Self::MAGIC,
+                actual,
+            }));
+        }
+        Self::decode_version(header[4], payload).map_err(envelope_error::<Self>)
+    }
+
+    /// Load object from either an envelope or a bare file written before envelopes.
+    ///
+    /// If the file doesn't start with [`Envelope::MAGIC`], it's decoded
+    /// by [`Envelope::decode_version`] as the version 0.
+    /// A bare file may start with the magic bytes by accident,
+    /// so if the envelope cannot be decoded, it's also tried as the version 0.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Envelope::decode_envelope`]. If both attempts fail,
+    /// the error is the one from decoding the envelope.
+    fn decode_compat(s: &'a [u8]) -> Result<Self, Error> {
+        if !s.starts_with(&Self::MAGIC) {
+            return Self::decode_version(0, s).map_err(envelope_error::<Self>);
+        }
+        match Self::decode_envelope(s) {
+            Ok(val) => Ok(val),
+            Err(err) => Self::decode_version(0, s).map_err(|_| err),
+        }
+    }
+
+    /// Encode the object with the envelope header as a Vec.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Encode::encode_vec`].
+    fn encode_envelope_vec(&self) -> Result<Vec<u8>, postcard::Error> {
+        let mut buf = Vec::with_capacity(self.envelope_size());
+        buf.extend_from_slice(&Self::MAGIC);
+        buf.push(Self::VERSION);
+        postcard::to_extend(self, buf)
+    }
+
+    /// Encode the object with the envelope header using the buffer.
+    ///
+    /// Use [`Envelope::envelope_size`] to calculate the required buffer size.
+    ///
+    /// # Errors
+    ///
+    /// May return an error if the buffer is not big enough.
+    fn encode_envelope_buf<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], postcard::Error> {
+        if buf.len() < ENVELOPE_HEADER_SIZE {
+            return Err(postcard::Error::SerializeBufferFull);
+        }
+        buf[..4].copy_from_slice(&Self::MAGIC);
+        buf[4] = Self::VERSION;
+        let size = postcard::to_slice(self, &mut buf[ENVELOPE_HEADER_SIZE..])?.len();
+        Ok(&mut buf[..ENVELOPE_HEADER_SIZE + size])
+    }
+
+    /// Calculate the buffer size required to encode the object with the envelope.
+    #[must_use]
+    fn envelope_size(&self) -> usize {
+        ENVELOPE_HEADER_SIZE + self.size()
+    }
+}
+
+const fn envelope_error<'a, T: Envelope<'a>>(error: EnvelopeError) -> Error {
+    error.for_file(T::FILE)
+}
+
+/// Like [`postcard::from_bytes`] but fails if not all bytes are consumed.
+///
+/// Useful for telling apart older layouts of the same type
+/// that differ only in the number of trailing fields.
+pub fn from_bytes_exact<'a, T: serde::Deserialize<'a>>(s: &'a [u8]) -> Result<T, postcard::Error> {
+    let (val, rest) = postcard::take_from_bytes(s)?;
+    if !rest.is_empty() {
+        return Err(postcard::Error::DeserializeBadEncoding);
+    }
+    Ok(val)
+}
@@ -0,0 +1,263 @@
+use crate::{
+    BadgeValidationError, BoardValidationError, DateError, EnvelopeError, ScoreError,
+    ThemeValidationError, ValidationError, Version,
+};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+/// Any error that can be returned by this crate.
+///
+/// All error types of the crate can be converted into it,
+/// with the context about which file or field failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Failed to encode or decode an object.
+    Postcard(postcard::Error),
+
+    /// Failed to decode the given file wrapped in an [`Envelope`][crate::Envelope].
+    Envelope {
+        file: &'static str,
+        error: EnvelopeError,
+    },
+
+    /// The given field has an invalid value.
+    Field {
+        field: &'static str,
+        error: ValidationError,
+    },
+
+    /// The badge with the given index is invalid.
+    Badge {
+        index: usize,
+        error: BadgeValidationError,
+    },
+
+    /// The board with the given index is invalid.
+    Board {
+        index: usize,
+        error: BoardValidationError,
+    },
+
+    /// The given field of the translation with the given index is invalid.
+    Translation {
+        index: usize,
+        field: &'static str,
+        error: ValidationError,
+    },
+
+    /// The color scheme is invalid.
+    Theme(ThemeValidationError),
+
+    /// The date is invalid.
+    Date(DateError),
+
+    /// The given field has an invalid date.
+    DateField {
+        field: &'static str,
+        error: DateError,
+    },
+
+    /// The score doesn't fit the board.
+    Score(ScoreError),
+
+    /// The app requires a newer runtime than the one available.
+    RuntimeTooOld { required: Version, actual: Version },
+
+    /// Several errors found at once, like by [`Meta::validate`][crate::Meta::validate].
+    Multiple(Vec<Self>),
+}
+
+impl From<postcard::Error> for Error {
+    fn from(value: postcard::Error) -> Self {
+        Self::Postcard(value)
+    }
+}
+
+impl From<ThemeValidationError> for Error {
+    fn from(value: ThemeValidationError) -> Self {
+        Self::Theme(value)
+    }
+}
+
+impl From<DateError> for Error {
+    fn from(value: DateError) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<ScoreError> for Error {
+    fn from(value: ScoreError) -> Self {
+        Self::Score(value)
+    }
+}
+
+/// Allows using `?` on functions returning all found errors.
+///
+/// A single error is unwrapped from the list.
+/// The list must not be empty: validators return `Ok` when no errors are found.
+impl From<Vec<Self>> for Error {
+    fn from(mut value: Vec<Self>) -> Self {
+        debug_assert!(!value.is_empty(), "no errors to convert");
+        if value.len() == 1 {
+            return value.remove(0);
+        }
+        Self::Multiple(value)
+    }
+}
+
+/// Turn all errors found by a validator into a result.
+///
+/// Guarantees that the list of errors is never empty,
+/// so it can be safely converted into [`Error`].
+///
+/// # Errors
+///
+/// Returns the given errors if there are any.
+pub fn collect(errors: Vec<Error>) -> Result<(), Vec<Error>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Postcard(error) => write!(f, "{error}"),
+            Self::Envelope { file, error } => write!(f, "invalid {file}: {error}"),
+            Self::Field { field, error } => write!(f, "invalid {field}: {error}"),
+            Self::Badge { index, error } => write!(f, "invalid badge #{index}: {error}"),
+            Self::Board { index, error } => write!(f, "invalid board #{index}: {error}"),
+            Self::Translation {
+                index,
+                field,
+                error,
+            } => write!(f, "invalid translation #{index} {field}: {error}"),
+            Self::Theme(error) => write!(f, "invalid theme: {error}"),
+            Self::Date(error) => write!(f, "invalid date: {error}"),
+            Self::DateField { field, error } => write!(f, "invalid {field}: {error}"),
+            Self::Score(error) => write!(f, "invalid score: {error}"),
+            Self::RuntimeTooOld { required, actual } => {
+                write!(f, "requires runtime {required} or newer, got {actual}")
+            }
+            Self::Multiple(errors) => {
+                write!(f, "{} errors found", errors.len())?;
+                for (i, error) in errors.iter().enumerate() {
+                    let sep = if i == 0 { ": " } else { "; " };
+                    write!(f, "{sep}{error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Postcard(error) => Some(error),
+            Self::Envelope { error, .. } => Some(error),
+            Self::Field { error, .. } | Self::Translation { error, .. } => Some(error),
+            Self::Badge { error, .. } => Some(error),
+            Self::Board { error, .. } => Some(error),
+            Self::Theme(error) => Some(error),
+            Self::Date(error) | Self::DateField { error, .. } => Some(error),
+            Self::Score(error) => Some(error),
+            Self::RuntimeTooOld { .. } | Self::Multiple(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Badge, Badges, Encode, Envelope, Meta, Stats, validate_id};
+    use alloc::borrow::Cow;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use core::error::Error as _;
+
+    #[test]
+    fn test_display() {
+        let err = ValidationError::Empty.for_field("app_id");
+        assert_eq!(err.to_string(), "invalid app_id: must not be empty");
+        let err = BadgeValidationError::EmptyName.at(2);
+        assert_eq!(err.to_string(), "invalid badge #2: name must not be empty");
+        let err = DateError::InvalidDay.for_field("installed_on");
+        assert_eq!(
+            err.to_string(),
+            "invalid installed_on: day is out of range for the month"
+        );
+        let err = EnvelopeError::TooShort.for_file("stats");
+        assert_eq!(err.to_string(), "invalid stats: file is too short");
+        let err = Error::from(vec![
+            BoardValidationError::EmptyName.at(0),
+            ValidationError::TooLong.for_field("app_name"),
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "2 errors found: invalid board #0: name must not be empty; invalid app_name: too long"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let err = ValidationError::Empty.for_field("app_id");
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), "must not be empty");
+        assert!(Error::Multiple(vec![]).source().is_none());
+        let err = Error::from(ThemeValidationError::BgOutOfRange);
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_collect() {
+        assert_eq!(collect(vec![]), Ok(()));
+        let errors = vec![ValidationError::Empty.for_field("app_id")];
+        assert_eq!(collect(errors.clone()), Err(errors));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "no errors to convert"]
+    fn test_from_empty() {
+        let _ = Error::from(vec![]);
+    }
+
+    #[test]
+    fn test_question_mark() {
+        fn check(raw: &[u8]) -> Result<(), Error> {
+            let meta = Meta::decode_compat(raw)?;
+            meta.validate()?;
+            validate_id(meta.app_id).map_err(|e| e.for_field("app_id"))?;
+            let badge = Badge {
+                position: 0,
+                xp: 0,
+                steps: 1,
+                hidden: 0,
+                name: "",
+                descr: "",
+            };
+            badge.validate().map_err(|e| e.at(0))?;
+            Badges::new(Cow::Owned(vec![badge])).validate()?;
+            Stats::decode(raw).map_err(|e| EnvelopeError::from(e).for_file(Stats::FILE))?;
+            Ok(())
+        }
+
+        let meta = Meta {
+            app_id: "app",
+            app_name: "App",
+            author_id: "author",
+            author_name: "Author",
+            ..Meta::default()
+        };
+        let raw = meta.encode_envelope_vec().unwrap();
+        assert_eq!(check(&raw), Err(BadgeValidationError::EmptyName.at(0)));
+        let err = check(b"").unwrap_err();
+        assert!(matches!(err, Error::Envelope { file: "_meta", .. }));
+        let meta = Meta::default();
+        let raw = meta.encode_envelope_vec().unwrap();
+        assert!(matches!(check(&raw), Err(Error::Multiple(_))));
+    }
+}
@@ -0,0 +1,122 @@
+//! Helpers for defining named bit flags.
+//!
+//! All flag types in the crate share the same rules, so they are generated here
+//! to not drift apart.
+
+/// Define an enum of named flags, each bound to a fixed bit index.
+///
+/// The enum gets `ALL`, `bit`, and a private `mask` helper
+/// returning the flag bit in the given integer type.
+macro_rules! flag_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: $repr:ty {
+            $(
+                $(#[$vmeta:meta])*
+                $variant:ident = $bit:literal,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        $vis enum $name {
+            $(
+                $(#[$vmeta])*
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// All known flags, in the order of their bits.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// The bit index of the flag.
+            ///
+            /// Never change the index of an existing flag, only add new ones.
+            #[must_use]
+            pub const fn bit(self) -> u32 {
+                match self {
+                    $(Self::$variant => $bit,)*
+                }
+            }
+
+            const fn mask(self) -> $repr {
+                1 << self.bit()
+            }
+        }
+    };
+}
+
+/// Define a newtype set of flags defined by [`flag_enum`].
+///
+/// Bits that are unknown to this version of the crate are preserved as-is,
+/// so that older software doesn't clear flags set by newer software.
+macro_rules! flag_set {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($repr:ty): $flag:ty;
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Unknown bits are preserved as-is.
+        #[derive(
+            serde::Serialize,
+            serde::Deserialize,
+            Clone,
+            Copy,
+            Debug,
+            Default,
+            Eq,
+            PartialEq,
+            Hash,
+        )]
+        $vis struct $name(pub $repr);
+
+        impl $name {
+            /// The empty set.
+            pub const NONE: Self = Self(0);
+
+            /// Check if the given flag is in the set.
+            #[must_use]
+            pub const fn contains(self, flag: $flag) -> bool {
+                self.0 & flag.mask() != 0
+            }
+
+            /// Add the given flag to the set.
+            pub const fn insert(&mut self, flag: $flag) {
+                self.0 |= flag.mask();
+            }
+
+            /// Remove the given flag from the set.
+            pub const fn remove(&mut self, flag: $flag) {
+                self.0 &= !flag.mask();
+            }
+
+            /// Add or remove the given flag.
+            pub const fn set(&mut self, flag: $flag, value: bool) {
+                if value {
+                    self.insert(flag);
+                } else {
+                    self.remove(flag);
+                }
+            }
+
+            /// Iterate over the known flags in the set.
+            pub fn iter(self) -> impl Iterator<Item = $flag> {
+                <$flag>::ALL.iter().copied().filter(move |f| self.contains(*f))
+            }
+        }
+
+        impl From<$repr> for $name {
+            fn from(value: $repr) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $repr {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
@@ -0,0 +1,210 @@
+use crate::{Error, Meta, ShortMeta, ValidationError, validate_id, validate_path_part};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// The full app ID: author ID and app ID.
+///
+/// Both IDs are always valid (see [`validate_id`]), so it's safe
+/// to use them to construct vFS paths.
+///
+/// Encoded as a pair of strings. Decoding fails if any of the IDs is not valid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "(String, String)", into = "(String, String)")]
+pub struct FullId {
+    author_id: String,
+    app_id: String,
+}
+
+impl FullId {
+    /// Create a new full ID from the given author ID and app ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] if any of the IDs is not valid.
+    pub fn new(author_id: &str, app_id: &str) -> Result<Self, Error> {
+        if let Err(error) = validate_id(author_id) {
+            return Err(Error::Field {
+                field: "author_id",
+                error,
+            });
+        }
+        if let Err(error) = validate_id(app_id) {
+            return Err(Error::Field {
+                field: "app_id",
+                error,
+            });
+        }
+        Ok(Self {
+            author_id: author_id.to_string(),
+            app_id: app_id.to_string(),
+        })
+    }
+
+    #[must_use]
+    pub fn author_id(&self) -> &str {
+        &self.author_id
+    }
+
+    #[must_use]
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// The directory with the app ROM: `roms/AUTHOR/APP`.
+    #[must_use]
+    pub fn rom_dir(&self) -> String {
+        format!("roms/{}/{}", self.author_id, self.app_id)
+    }
+
+    /// The directory with the app data: `data/AUTHOR/APP`.
+    #[must_use]
+    pub fn data_dir(&self) -> String {
+        format!("data/{}/{}", self.author_id, self.app_id)
+    }
+
+    /// The path to the given file in the app ROM directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] if the file name is not a valid path component.
+    pub fn rom_path(&self, file: &str) -> Result<String, Error> {
+        validate_file(file)?;
+        Ok(format!("{}/{file}", self.rom_dir()))
+    }
+
+    /// The path to the given file in the app data directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] if the file name is not a valid path component.
+    pub fn data_path(&self, file: &str) -> Result<String, Error> {
+        validate_file(file)?;
+        Ok(format!("{}/{file}", self.data_dir()))
+    }
+
+    /// The path to the [`Meta`] file: `roms/AUTHOR/APP/_meta`.
+    #[must_use]
+    pub fn meta_path(&self) -> String {
+        format!("{}/_meta", self.rom_dir())
+    }
+
+    /// The path to the [`Badges`][crate::Badges] file: `roms/AUTHOR/APP/_badges`.
+    #[must_use]
+    pub fn badges_path(&self) -> String {
+        format!("{}/_badges", self.rom_dir())
+    }
+
+    /// The path to the [`Boards`][crate::Boards] file: `roms/AUTHOR/APP/_boards`.
+    #[must_use]
+    pub fn boards_path(&self) -> String {
+        format!("{}/_boards", self.rom_dir())
+    }
+
+    /// The path to the [`Stats`][crate::Stats] file: `data/AUTHOR/APP/stats`.
+    #[must_use]
+    pub fn stats_path(&self) -> String {
+        format!("{}/stats", self.data_dir())
+    }
+}
+
+fn validate_file(file: &str) -> Result<(), Error> {
+    validate_path_part(file).map_err(|error| error.for_field("file"))
+}
+
+/// Formats the ID as `AUTHOR.APP`.
+impl Display for FullId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.author_id, self.app_id)
+    }
+}
+
+/// Parses the ID in `AUTHOR.APP` format.
+impl FromStr for FullId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((author_id, app_id)) = s.split_once('.') else {
+            return Err(Error::Field {
+                field: "full_id",
+                error: ValidationError::NoDot,
+            });
+        };
+        Self::new(author_id, app_id)
+    }
+}
+
+impl TryFrom<&Meta<'_>> for FullId {
+    type Error = Error;
+
+    fn try_from(value: &Meta<'_>) -> Result<Self, Self::Error> {
+        Self::new(value.author_id, value.app_id)
+    }
+}
+
+impl TryFrom<&ShortMeta<'_>> for FullId {
+    type Error = Error;
+
+    fn try_from(value: &ShortMeta<'_>) -> Result<Self, Self::Error> {
+        Self::new(value.author_id, value.app_id)
+    }
+}
+
+/// Convert from the (author ID, app ID) pair used in [`crate::serial`].
+impl TryFrom<(String, String)> for FullId {
+    type Error = Error;
+
+    fn try_from((author_id, app_id): (String, String)) -> Result<Self, Self::Error> {
+        Self::new(&author_id, &app_id)
+    }
+}
+
+/// Convert into the (author ID, app ID) pair used in [`crate::serial`].
+impl From<FullId> for (String, String) {
+    fn from(value: FullId) -> Self {
+        (value.author_id, value.app_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let id: FullId = "lux.snek".parse().unwrap();
+        assert_eq!(id.author_id(), "lux");
+        assert_eq!(id.app_id(), "snek");
+        assert_eq!(id.to_string(), "lux.snek");
+
+        assert!("lux".parse::<FullId>().is_err());
+        assert!("lux.".parse::<FullId>().is_err());
+        assert!(".snek".parse::<FullId>().is_err());
+        assert!("lux.snek.2".parse::<FullId>().is_err());
+        assert!("../..".parse::<FullId>().is_err());
+    }
+
+    #[test]
+    fn test_paths() {
+        let id = FullId::new("lux", "snek").unwrap();
+        assert_eq!(id.meta_path(), "roms/lux/snek/_meta");
+        assert_eq!(id.stats_path(), "data/lux/snek/stats");
+        assert_eq!(id.rom_path("_bin").unwrap(), "roms/lux/snek/_bin");
+        assert_eq!(id.data_path("etc").unwrap(), "data/lux/snek/etc");
+        assert!(id.data_path("../../sys/config").is_err());
+        assert!(id.rom_path("").is_err());
+    }
+
+    #[test]
+    fn test_from_meta() {
+        let meta = ShortMeta {
+            app_id: "snek",
+            author_id: "lux",
+        };
+        let id = FullId::try_from(&meta).unwrap();
+        let pair: (String, String) = id.into();
+        assert_eq!(pair, ("lux".to_string(), "snek".to_string()));
+    }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+flag_enum! {
+    /// A physical button on the device.
+    ///
+    /// The bit layout is defined by this crate, it's not derived from hardware.
+    /// Firmware and clients must build and read the byte through [`Buttons`]
+    /// instead of hardcoding bit positions.
+    pub enum Button: u8 {
+        /// The bottom button of the diamond (A on Xbox-like gamepads).
+        S = 0,
+        /// The right button of the diamond (B on Xbox-like gamepads).
+        E = 1,
+        /// The left button of the diamond (X on Xbox-like gamepads).
+        W = 2,
+        /// The top button of the diamond (Y on Xbox-like gamepads).
+        N = 3,
+        /// The menu button.
+        Menu = 4,
+    }
+}
+
+flag_set! {
+    /// A set of pressed [`Button`]s.
+    pub struct Buttons(u8): Button;
+}
+
+impl Buttons {
+    /// Check if no buttons are pressed.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Buttons that are pressed now but weren't pressed in the previous state.
+    #[must_use]
+    pub const fn just_pressed(self, prev: Self) -> Self {
+        Self(self.0 & !prev.0)
+    }
+
+    /// Buttons that were pressed in the previous state but aren't pressed now.
+    #[must_use]
+    pub const fn just_released(self, prev: Self) -> Self {
+        Self(prev.0 & !self.0)
+    }
+}
+
+/// The direction on the touchpad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The range of raw touchpad coordinates.
+///
+/// It depends on the touchpad hardware and its driver in the IO chip firmware,
+/// so it's not hardcoded and must be provided by the caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PadRange {
+    /// The maximum raw x coordinate (the right edge).
+    pub max_x: u16,
+    /// The maximum raw y coordinate (the bottom edge).
+    pub max_y: u16,
+}
+
+/// The state of the touchpad and buttons at one moment.
+///
+/// Sent in [`spi::Response::Input`][crate::spi::Response::Input].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct InputState {
+    /// The raw touch coordinates on the pad, if the pad is touched.
+    ///
+    /// (0, 0) is the top-left corner. See [`PadRange`].
+    pub pad: Option<(u16, u16)>,
+
+    /// The pressed buttons.
+    pub buttons: Buttons,
+}
+
+impl InputState {
+    /// The maximum absolute value of normalized pad coordinates.
+    pub const NORM_MAX: i16 = 1000;
+
+    /// How far from the center the touch must be to register a [`Direction`].
+    ///
+    /// In normalized coordinates.
+    pub const DEAD_ZONE: i16 = 300;
+
+    /// Buttons that are pressed now but weren't pressed in the previous state.
+    #[must_use]
+    pub const fn just_pressed(&self, prev: &Self) -> Buttons {
+        self.buttons.just_pressed(prev.buttons)
+    }
+
+    /// Buttons that were pressed in the previous state but aren't pressed now.
+    #[must_use]
+    pub const fn just_released(&self, prev: &Self) -> Buttons {
+        self.buttons.just_released(prev.buttons)
+    }
+
+    /// Pad coordinates relative to the pad center.
+    ///
+    /// Both coordinates are in the `-NORM_MAX..=NORM_MAX` range.
+    /// Positive x is right, positive y is up.
+    /// Out-of-range raw values are clamped.
+    #[must_use]
+    pub fn pad_normalized(&self, range: PadRange) -> Option<(i16, i16)> {
+        let (x, y) = self.pad?;
+        Some((normalize(x, range.max_x), -normalize(y, range.max_y)))
+    }
+
+    /// The direction in which the pad is touched, if outside of [`InputState::DEAD_ZONE`].
+    ///
+    /// If the touch is diagonal, the axis with the bigger offset wins.
+    #[must_use]
+    pub fn direction(&self, range: PadRange) -> Option<Direction> {
+        let (x, y) = self.pad_normalized(range)?;
+        if x.abs() < Self::DEAD_ZONE && y.abs() < Self::DEAD_ZONE {
+            return None;
+        }
+        let dir = if x.abs() > y.abs() {
+            if x > 0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if y > 0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+        Some(dir)
+    }
+}
+
+fn normalize(raw: u16, max: u16) -> i16 {
+    if max == 0 {
+        return 0;
+    }
+    let raw = i32::from(raw.min(max));
+    let max = i32::from(max);
+    let norm = (raw * 2 - max) * i32::from(InputState::NORM_MAX) / max;
+    // The value is clamped above, so it always fits.
+    i16::try_from(norm).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::Encode;
+    use crate::spi::Response;
+
+    #[test]
+    fn test_buttons() {
+        let mut buttons = Buttons::NONE;
+        buttons.insert(Button::S);
+        buttons.insert(Button::Menu);
+        assert!(buttons.contains(Button::S));
+        assert!(!buttons.contains(Button::N));
+        assert_eq!(buttons.0, 0b1_0001);
+        let pressed: alloc::vec::Vec<_> = buttons.iter().collect();
+        assert_eq!(pressed, [Button::S, Button::Menu]);
+        buttons.remove(Button::S);
+        assert_eq!(buttons, Buttons(0b1_0000));
+
+        let prev = Buttons(0b0011);
+        let now = Buttons(0b1_0110);
+        assert_eq!(now.just_pressed(prev), Buttons(0b1_0100));
+        assert_eq!(now.just_released(prev), Buttons(0b0001));
+    }
+
+    #[test]
+    fn test_pad() {
+        let range = PadRange {
+            max_x: 1000,
+            max_y: 1000,
+        };
+        let mut state = InputState::default();
+        assert_eq!(state.pad_normalized(range), None);
+        assert_eq!(state.direction(range), None);
+
+        state.pad = Some((500, 500));
+        assert_eq!(state.pad_normalized(range), Some((0, 0)));
+        assert_eq!(state.direction(range), None);
+        state.pad = Some((0, 1000));
+        assert_eq!(state.pad_normalized(range), Some((-1000, -1000)));
+        state.pad = Some((2000, 0));
+        assert_eq!(state.pad_normalized(range), Some((1000, 1000)));
+
+        state.pad = Some((900, 600));
+        assert_eq!(state.direction(range), Some(Direction::Right));
+        state.pad = Some((400, 100));
+        assert_eq!(state.direction(range), Some(Direction::Up));
+        state.pad = Some((500, 950));
+        assert_eq!(state.direction(range), Some(Direction::Down));
+        state.pad = Some((100, 500));
+        assert_eq!(state.direction(range), Some(Direction::Left));
+
+        let wide = PadRange {
+            max_x: 4000,
+            max_y: 200,
+        };
+        state.pad = Some((2000, 100));
+        assert_eq!(state.pad_normalized(wide), Some((0, 0)));
+        state.pad = Some((3800, 100));
+        assert_eq!(state.direction(wide), Some(Direction::Right));
+        let empty = PadRange { max_x: 0, max_y: 0 };
+        assert_eq!(state.pad_normalized(empty), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_wire_compat() {
+        let state = InputState {
+            pad: Some((300, 700)),
+            buttons: Buttons(0b1010),
+        };
+        let raw = Response::Input(state).encode_vec().unwrap();
+        let legacy = postcard::to_allocvec(&(9u8, Some((300u16, 700u16)), 0b1010u8)).unwrap();
+        assert_eq!(raw, legacy);
+        assert_eq!(Response::decode(&raw).unwrap(), Response::Input(state));
+    }
+}
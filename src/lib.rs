@@ -10,22 +10,39 @@
 
 extern crate alloc;
 
+#[macro_use]
+mod flags;
+
+mod app_index;
 mod badges;
 mod boards;
+mod date;
 mod encode;
+mod error;
+mod full_id;
+mod input;
 pub mod manuals;
 mod meta;
 pub mod serial;
 mod settings;
 pub mod spi;
 mod stats;
+mod theme;
 mod validators;
+mod version;
 pub mod wifi;
 
+pub use app_index::{AppEntry, AppIndex};
 pub use badges::*;
 pub use boards::*;
-pub use encode::*;
-pub use meta::{Meta, ShortMeta};
+pub use date::*;
+pub use encode::{ENVELOPE_HEADER_SIZE, Encode, Envelope, EnvelopeError};
+pub use error::Error;
+pub use full_id::FullId;
+pub use input::{Button, Buttons, Direction, InputState, PadRange};
+pub use meta::{Meta, Permission, Permissions, ShortMeta, Translation};
 pub use settings::*;
 pub use stats::*;
+pub use theme::*;
 pub use validators::*;
+pub use version::Version;
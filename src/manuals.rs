@@ -2,7 +2,7 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
-use crate::Encode;
+use crate::{Encode, Theme};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Manual {
@@ -18,11 +18,9 @@ pub struct Page {
     /// Show the page only when the given badge is unlocked.
     pub badge: Option<u8>,
     /// Show the page only when the given score is reached on the given board.
-    pub score: Option<(u8, i16)>,
-    /// Encoded color scheme.
-    ///
-    /// It's encoded the same way as in [Settings][crate::Settings].
-    pub theme: Option<u32>,
+    pub score: Option<(u8, i32)>,
+    /// Color scheme to use for the page instead of the one from [Settings][crate::Settings].
+    pub theme: Option<Theme>,
     pub content: Vec<Block>,
 }
 
@@ -1,6 +1,13 @@
 #![allow(clippy::module_name_repetitions)]
 
-use crate::Encode;
+use crate::encode::from_bytes_exact;
+use crate::error::collect;
+use crate::{
+    Encode, Envelope, EnvelopeError, Error, RESERVED_AUTHOR_IDS, ValidationError, Version,
+    validate_id, validate_name,
+};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -12,15 +19,394 @@ pub struct Meta<'a> {
     pub author_name: &'a str,
     /// Launcher is the app that starts first when runtime is launched.
     pub launcher: bool,
-    /// Let the app to use privileged and dangerous runtime API.
-    pub sudo: bool,
+    /// Privileged and dangerous runtime APIs that the app is allowed to use.
+    pub permissions: Permissions,
     /// The ever-incrementing version number of the app build.
     /// Used by netplay to ensure both devices running the same version.
     pub version: u32,
+    /// The minimum number of players required to play the app.
+    ///
+    /// Greater than 1 for multiplayer-only apps.
+    pub min_players: u8,
+    /// The maximum number of players that the app supports.
+    ///
+    /// Equal to 1 for single-player apps.
+    pub max_players: u8,
+    /// The minimum version of the runtime required to run the app.
+    ///
+    /// See [`DeviceInfo::main_version`][crate::DeviceInfo::main_version].
+    pub min_runtime: Version,
+    /// Translations of the app name and description.
+    ///
+    /// At most one entry per language.
+    #[serde(borrow)]
+    pub translations: Cow<'a, [Translation<'a>]>,
+}
+
+/// The app name and description in a specific language.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Translation<'a> {
+    /// A two-letter ASCII ISO 639 Set 1 language code.
+    ///
+    /// The same as [`Settings::lang`][crate::Settings::lang].
+    pub lang: [u8; 2],
+    /// The translated app name. If empty, [`Meta::app_name`] is used.
+    pub name: &'a str,
+    /// The app description. May be empty.
+    pub descr: &'a str,
+}
+
+/// The values for the fields that are missing in older files.
+impl Default for Meta<'_> {
+    fn default() -> Self {
+        Self {
+            app_id: "",
+            app_name: "",
+            author_id: "",
+            author_name: "",
+            launcher: false,
+            permissions: Permissions::NONE,
+            version: 0,
+            // Older apps didn't declare the number of players,
+            // so don't put any restrictions on them.
+            min_players: 1,
+            max_players: u8::MAX,
+            min_runtime: Version::new(0, 0, 0),
+            translations: Cow::Borrowed(&[]),
+        }
+    }
+}
+
+impl Meta<'_> {
+    /// Check if the app is allowed to use the given privileged runtime API.
+    #[must_use]
+    pub const fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    /// The app name in the given language.
+    ///
+    /// Falls back to [`Meta::app_name`] if there is no translation.
+    #[must_use]
+    pub fn name_for(&self, lang: [u8; 2]) -> &str {
+        match self.translation(lang) {
+            Some(t) if !t.name.is_empty() => t.name,
+            _ => self.app_name,
+        }
+    }
+
+    /// The app description in the given language, if available.
+    #[must_use]
+    pub fn descr_for(&self, lang: [u8; 2]) -> Option<&str> {
+        match self.translation(lang) {
+            Some(t) if !t.descr.is_empty() => Some(t.descr),
+            _ => None,
+        }
+    }
+
+    fn translation(&self, lang: [u8; 2]) -> Option<&Translation<'_>> {
+        self.translations.iter().find(|t| t.lang == lang)
+    }
+
+    /// Check if the app can run on the runtime of the given version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RuntimeTooOld`] if the runtime is older than [`Meta::min_runtime`].
+    pub fn check_runtime(&self, runtime: Version) -> Result<(), Error> {
+        if runtime < self.min_runtime {
+            return Err(Error::RuntimeTooOld {
+                required: self.min_runtime,
+                actual: runtime,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check if the app can be played by the given number of players.
+    #[must_use]
+    pub const fn allows_players(&self, players: u8) -> bool {
+        players >= self.min_players && players <= self.max_players
+    }
+
+    /// Validate all fields.
+    ///
+    /// Third-party apps cannot use [`RESERVED_AUTHOR_IDS`].
+    /// So, make sure to not call it for system apps bundled with the firmware.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] for every invalid field
+    /// and [`Error::Translation`] for every invalid translation field.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        validate_full_id(&mut errors, self.author_id, self.app_id);
+        if let Err(error) = validate_name(self.app_name) {
+            errors.push(Error::Field {
+                field: "app_name",
+                error,
+            });
+        }
+        if let Err(error) = validate_name(self.author_name) {
+            errors.push(Error::Field {
+                field: "author_name",
+                error,
+            });
+        }
+        if self.min_players == 0 {
+            errors.push(Error::Field {
+                field: "min_players",
+                error: ValidationError::OutOfRange,
+            });
+        }
+        if self.max_players < self.min_players {
+            errors.push(Error::Field {
+                field: "max_players",
+                error: ValidationError::LessThanMin,
+            });
+        }
+        for (i, t) in self.translations.iter().enumerate() {
+            if let Err(errs) = t.validate(i) {
+                errors.extend(errs);
+            }
+            let prev = &self.translations[..i];
+            if prev.iter().any(|p| p.lang == t.lang) {
+                errors.push(Error::Translation {
+                    index: i,
+                    field: "lang",
+                    error: ValidationError::Duplicate,
+                });
+            }
+        }
+        collect(errors)
+    }
+}
+
+impl Translation<'_> {
+    /// Validate the translation.
+    ///
+    /// The name is validated the same way as [`Meta::app_name`].
+    /// The index is the position of the translation in [`Meta::translations`],
+    /// used only to tell which translation is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Translation`] for every invalid field.
+    pub fn validate(&self, index: usize) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut push = |field, error| {
+            errors.push(Error::Translation {
+                index,
+                field,
+                error,
+            });
+        };
+        if let Some(&c) = self.lang.iter().find(|c| !c.is_ascii_lowercase()) {
+            push("lang", ValidationError::InvalidChar(c));
+        }
+        if !self.name.is_empty()
+            && let Err(error) = validate_name(self.name)
+        {
+            push("name", error);
+        }
+        if self.descr.len() > 256 {
+            push("descr", ValidationError::TooLong);
+        }
+        collect(errors)
+    }
 }
 
 impl<'a> Encode<'a> for Meta<'a> {}
 
+impl<'a> Envelope<'a> for Meta<'a> {
+    const FILE: &'static str = "_meta";
+    const MAGIC: [u8; 4] = *b"FFMT";
+    const VERSION: u8 = 5;
+
+    fn decode_version(version: u8, payload: &'a [u8]) -> Result<Self, EnvelopeError> {
+        // Bare files are either written by older tools in the first layout
+        // or by `Encode::encode_vec` in the current one. The layouts differ in length,
+        // so strict decoding never confuses them. It's important because reading
+        // `permissions` as `sudo` would grant the app all permissions.
+        if version == 0
+            && let Ok(meta) = from_bytes_exact(payload)
+        {
+            return Ok(meta);
+        }
+        match version {
+            0 | 1 => Ok(from_bytes_exact::<MetaV1<'a>>(payload)?.into()),
+            2 => Ok(from_bytes_exact::<MetaV2<'a>>(payload)?.into()),
+            3 => Ok(from_bytes_exact::<MetaV3<'a>>(payload)?.into()),
+            4 => Ok(from_bytes_exact::<MetaV4<'a>>(payload)?.into()),
+            Self::VERSION => Ok(postcard::from_bytes(payload)?),
+            _ => Err(EnvelopeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// The layout of [`Meta`] before [`Meta::permissions`] replaced `sudo`.
+#[derive(Deserialize)]
+struct MetaV1<'a> {
+    app_id: &'a str,
+    app_name: &'a str,
+    author_id: &'a str,
+    author_name: &'a str,
+    launcher: bool,
+    sudo: bool,
+    version: u32,
+}
+
+impl<'a> From<MetaV1<'a>> for Meta<'a> {
+    fn from(v: MetaV1<'a>) -> Self {
+        let permissions = if v.sudo {
+            Permissions::ALL
+        } else {
+            Permissions::NONE
+        };
+        Self {
+            app_id: v.app_id,
+            app_name: v.app_name,
+            author_id: v.author_id,
+            author_name: v.author_name,
+            launcher: v.launcher,
+            permissions,
+            version: v.version,
+            ..Self::default()
+        }
+    }
+}
+
+/// The layout of [`Meta`] before [`Meta::min_players`] and [`Meta::max_players`] were added.
+#[derive(Deserialize)]
+struct MetaV2<'a> {
+    app_id: &'a str,
+    app_name: &'a str,
+    author_id: &'a str,
+    author_name: &'a str,
+    launcher: bool,
+    permissions: Permissions,
+    version: u32,
+}
+
+impl<'a> From<MetaV2<'a>> for Meta<'a> {
+    fn from(v: MetaV2<'a>) -> Self {
+        Self {
+            app_id: v.app_id,
+            app_name: v.app_name,
+            author_id: v.author_id,
+            author_name: v.author_name,
+            launcher: v.launcher,
+            permissions: v.permissions,
+            version: v.version,
+            ..Self::default()
+        }
+    }
+}
+
+/// The layout of [`Meta`] before [`Meta::min_runtime`] was added.
+#[derive(Deserialize)]
+struct MetaV3<'a> {
+    app_id: &'a str,
+    app_name: &'a str,
+    author_id: &'a str,
+    author_name: &'a str,
+    launcher: bool,
+    permissions: Permissions,
+    version: u32,
+    min_players: u8,
+    max_players: u8,
+}
+
+impl<'a> From<MetaV3<'a>> for Meta<'a> {
+    fn from(v: MetaV3<'a>) -> Self {
+        Self {
+            app_id: v.app_id,
+            app_name: v.app_name,
+            author_id: v.author_id,
+            author_name: v.author_name,
+            launcher: v.launcher,
+            permissions: v.permissions,
+            version: v.version,
+            min_players: v.min_players,
+            max_players: v.max_players,
+            ..Self::default()
+        }
+    }
+}
+
+/// The layout of [`Meta`] before [`Meta::translations`] were added.
+#[derive(Deserialize)]
+struct MetaV4<'a> {
+    app_id: &'a str,
+    app_name: &'a str,
+    author_id: &'a str,
+    author_name: &'a str,
+    launcher: bool,
+    permissions: Permissions,
+    version: u32,
+    min_players: u8,
+    max_players: u8,
+    min_runtime: Version,
+}
+
+impl<'a> From<MetaV4<'a>> for Meta<'a> {
+    fn from(v: MetaV4<'a>) -> Self {
+        Self {
+            app_id: v.app_id,
+            app_name: v.app_name,
+            author_id: v.author_id,
+            author_name: v.author_name,
+            launcher: v.launcher,
+            permissions: v.permissions,
+            version: v.version,
+            min_players: v.min_players,
+            max_players: v.max_players,
+            min_runtime: v.min_runtime,
+            ..Self::default()
+        }
+    }
+}
+
+flag_enum! {
+    /// A privileged runtime API that an app may be allowed to use.
+    pub enum Permission: u32 {
+        /// Read files of other apps.
+        ReadApps = 0,
+        /// Change system settings (`sys/config`).
+        WriteSettings = 1,
+        /// Open TCP connections and access the network.
+        Network = 2,
+        /// Scan for, connect to, and disconnect from Wi-Fi access points.
+        Wifi = 3,
+        /// Flash a new firmware and switch partitions.
+        Firmware = 4,
+    }
+}
+
+flag_set! {
+    /// A set of [`Permission`]s.
+    pub struct Permissions(u32): Permission;
+}
+
+impl Permissions {
+    /// All permissions, including the ones that might be added in the future.
+    ///
+    /// That's what apps with the legacy `sudo` flag get.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Create a set with the given permissions.
+    #[must_use]
+    pub const fn from_slice(permissions: &[Permission]) -> Self {
+        let mut res = Self::NONE;
+        let mut i = 0;
+        while i < permissions.len() {
+            res.insert(permissions[i]);
+            i += 1;
+        }
+        res
+    }
+}
+
 /// The full app ID.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct ShortMeta<'a> {
@@ -28,8 +414,43 @@ pub struct ShortMeta<'a> {
     pub author_id: &'a str,
 }
 
+impl ShortMeta<'_> {
+    /// Validate all fields.
+    ///
+    /// Same as [`Meta::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] for every invalid field.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        validate_full_id(&mut errors, self.author_id, self.app_id);
+        collect(errors)
+    }
+}
+
 impl<'a> Encode<'a> for ShortMeta<'a> {}
 
+fn validate_full_id(errors: &mut Vec<Error>, author_id: &str, app_id: &str) {
+    if let Err(error) = validate_id(app_id) {
+        errors.push(Error::Field {
+            field: "app_id",
+            error,
+        });
+    }
+    if let Err(error) = validate_id(author_id) {
+        errors.push(Error::Field {
+            field: "author_id",
+            error,
+        });
+    } else if RESERVED_AUTHOR_IDS.contains(&author_id) {
+        errors.push(Error::Field {
+            field: "author_id",
+            error: ValidationError::Reserved,
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,8 +463,16 @@ mod tests {
             author_id: "some-author-id",
             author_name: "Some Author Name",
             launcher: false,
-            sudo: false,
+            permissions: Permissions::NONE,
             version: 12,
+            min_players: 1,
+            max_players: 4,
+            min_runtime: Version::new(0, 9, 2),
+            translations: Cow::Owned(vec![Translation {
+                lang: *b"nl",
+                name: "Een App",
+                descr: "Beschrijving",
+            }]),
         };
         let mut buf = vec![0; given.size()];
         let raw = given.encode_buf(&mut buf).unwrap();
@@ -62,4 +491,267 @@ mod tests {
         let actual = ShortMeta::decode(raw).unwrap();
         assert_eq!(given, actual);
     }
+
+    #[test]
+    fn test_meta_validate() {
+        let mut meta = Meta {
+            app_id: "some-app-id",
+            app_name: "Some App Name",
+            author_id: "some-author-id",
+            author_name: "Some Author Name",
+            launcher: false,
+            permissions: Permissions::NONE,
+            version: 12,
+            min_players: 1,
+            max_players: 4,
+            min_runtime: Version::new(0, 9, 2),
+            translations: Cow::Owned(vec![Translation {
+                lang: *b"nl",
+                name: "Een App",
+                descr: "Beschrijving",
+            }]),
+        };
+        assert!(meta.validate().is_ok());
+
+        meta.app_id = "../../etc";
+        meta.author_id = "sys";
+        meta.author_name = "";
+        meta.min_players = 5;
+        let errors = meta.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                Error::Field {
+                    field: "app_id",
+                    error: ValidationError::InvalidChar(b'.')
+                },
+                Error::Field {
+                    field: "author_id",
+                    error: ValidationError::Reserved
+                },
+                Error::Field {
+                    field: "author_name",
+                    error: ValidationError::Empty
+                },
+                Error::Field {
+                    field: "max_players",
+                    error: ValidationError::LessThanMin
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_short_meta_validate() {
+        let meta = ShortMeta {
+            app_id: "app",
+            author_id: "author",
+        };
+        assert!(meta.validate().is_ok());
+        let meta = ShortMeta {
+            app_id: "",
+            author_id: "Author",
+        };
+        assert_eq!(meta.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_permissions() {
+        let mut perms = Permissions::from_slice(&[Permission::Network, Permission::Wifi]);
+        assert!(perms.contains(Permission::Network));
+        assert!(perms.contains(Permission::Wifi));
+        assert!(!perms.contains(Permission::Firmware));
+        perms.remove(Permission::Wifi);
+        assert!(!perms.contains(Permission::Wifi));
+        perms.insert(Permission::Firmware);
+        assert!(perms.contains(Permission::Firmware));
+        assert!(Permissions::ALL.contains(Permission::ReadApps));
+        assert!(!Permissions::NONE.contains(Permission::ReadApps));
+    }
+
+    #[test]
+    fn test_decode_v1() {
+        let old = ("app", "App", "author", "Author", false, true, 3u32);
+        let raw = postcard::to_allocvec(&old).unwrap();
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.app_id, "app");
+        assert_eq!(meta.author_name, "Author");
+        assert_eq!(meta.permissions, Permissions::ALL);
+        assert!(meta.has_permission(Permission::Firmware));
+        assert_eq!(meta.version, 3);
+
+        let old = ("app", "App", "author", "Author", false, false, 3u32);
+        let mut raw = postcard::to_allocvec(&old).unwrap();
+        raw.splice(0..0, *b"FFMT\x01");
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.permissions, Permissions::NONE);
+        assert_eq!(meta.version, 3);
+        assert!(meta.allows_players(1));
+        assert!(meta.allows_players(8));
+    }
+
+    #[test]
+    fn test_decode_bare_current() {
+        let given = Meta {
+            app_id: "app",
+            app_name: "App",
+            author_id: "author",
+            author_name: "Author",
+            permissions: Permissions::from_slice(&[Permission::ReadApps]),
+            ..Meta::default()
+        };
+        let raw = given.encode_vec().unwrap();
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.permissions, Permissions(1));
+        assert_eq!(meta, given);
+    }
+
+    #[test]
+    fn test_decode_v2() {
+        let old = ("app", "App", "author", "Author", false, 5u32, 3u32);
+        let mut raw = postcard::to_allocvec(&old).unwrap();
+        raw.splice(0..0, *b"FFMT\x02");
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.permissions, Permissions(5));
+        assert_eq!(meta.version, 3);
+        assert_eq!(meta.min_players, 1);
+        assert_eq!(meta.max_players, u8::MAX);
+    }
+
+    #[test]
+    fn test_players() {
+        let meta = Meta {
+            min_players: 2,
+            max_players: 4,
+            ..Meta::default()
+        };
+        assert!(!meta.allows_players(0));
+        assert!(!meta.allows_players(1));
+        assert!(meta.allows_players(2));
+        assert!(meta.allows_players(4));
+        assert!(!meta.allows_players(5));
+    }
+
+    #[test]
+    fn test_check_runtime() {
+        let meta = Meta {
+            min_runtime: Version::new(0, 9, 2),
+            ..Meta::default()
+        };
+        assert!(meta.check_runtime(Version::new(0, 9, 2)).is_ok());
+        assert!(meta.check_runtime(Version::new(0, 10, 0)).is_ok());
+        assert!(meta.check_runtime(Version::new(1, 0, 0)).is_ok());
+        let err = meta.check_runtime(Version::new(0, 9, 1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "requires runtime 0.9.2 or newer, got 0.9.1"
+        );
+        assert!(Meta::default().check_runtime(Version::default()).is_ok());
+    }
+
+    #[test]
+    fn test_decode_v3() {
+        let old = (
+            "app", "App", "author", "Author", false, 0u32, 3u32, 1u8, 2u8,
+        );
+        let mut raw = postcard::to_allocvec(&old).unwrap();
+        raw.splice(0..0, *b"FFMT\x03");
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.max_players, 2);
+        assert_eq!(meta.min_runtime, Version::default());
+    }
+
+    #[test]
+    fn test_translations() {
+        let meta = Meta {
+            app_name: "Snake",
+            translations: Cow::Owned(vec![
+                Translation {
+                    lang: *b"nl",
+                    name: "Slang",
+                    descr: "Eet de appels",
+                },
+                Translation {
+                    lang: *b"de",
+                    name: "",
+                    descr: "Iss die Äpfel",
+                },
+            ]),
+            ..Meta::default()
+        };
+        assert_eq!(meta.name_for(*b"nl"), "Slang");
+        assert_eq!(meta.name_for(*b"de"), "Snake");
+        assert_eq!(meta.name_for(*b"en"), "Snake");
+        assert_eq!(meta.descr_for(*b"de"), Some("Iss die Äpfel"));
+        assert_eq!(meta.descr_for(*b"en"), None);
+    }
+
+    #[test]
+    fn test_translations_validate() {
+        let valid = Translation {
+            lang: *b"nl",
+            name: "Slang",
+            descr: "",
+        };
+        assert!(valid.validate(0).is_ok());
+        let invalid = Translation {
+            lang: *b"NL",
+            name: " Slang",
+            ..valid.clone()
+        };
+        assert_eq!(
+            invalid.validate(2),
+            Err(vec![
+                Error::Translation {
+                    index: 2,
+                    field: "lang",
+                    error: ValidationError::InvalidChar(b'N'),
+                },
+                Error::Translation {
+                    index: 2,
+                    field: "name",
+                    error: ValidationError::InvalidFirstChar(b' '),
+                },
+            ])
+        );
+
+        let meta = Meta {
+            app_id: "snake",
+            app_name: "Snake",
+            author_id: "lux",
+            author_name: "Lux",
+            translations: Cow::Owned(vec![valid.clone(), valid]),
+            ..Meta::default()
+        };
+        let errors = meta.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [Error::Translation {
+                index: 1,
+                field: "lang",
+                error: ValidationError::Duplicate
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_v4() {
+        let old = (
+            "app",
+            "App",
+            "author",
+            "Author",
+            false,
+            0u32,
+            3u32,
+            1u8,
+            2u8,
+            (1u8, 2u8, 3u8),
+        );
+        let mut raw = postcard::to_allocvec(&old).unwrap();
+        raw.splice(0..0, *b"FFMT\x04");
+        let meta = Meta::decode_compat(&raw).unwrap();
+        assert_eq!(meta.min_runtime, Version::new(1, 2, 3));
+        assert!(meta.translations.is_empty());
+    }
 }
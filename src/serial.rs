@@ -6,6 +6,7 @@
 //! Unlike in multiplayer (which is peer-to-peer), this is asymmetric communication.
 //! Clients (desktop app, CLI, etc) send [`Request`]s
 //! and the runtime (device or emulator) sends back [`Response`]s.
+use crate::Buttons;
 use crate::encode::Encode;
 use alloc::boxed::Box;
 use alloc::string::String;
@@ -38,7 +39,7 @@ pub enum Request {
     Exit,
 
     /// Send buttons input.
-    Buttons(u8),
+    Buttons(Buttons),
 
     /// Send data into the running app.
     Data(Box<[u8]>),
@@ -1,11 +1,16 @@
-use crate::encode::Encode;
+use crate::encode::{Encode, Envelope, EnvelopeError, from_bytes_exact};
+use crate::{Theme, Version};
 use alloc::string::{String, ToString};
 use serde::{Deserialize, Serialize};
 
 /// System settings. Stored in `sys/config`.
 ///
-/// Since we don't have a realiable vesioning for the system config,
-/// some of the settings are added "just in case" and might be not used yet
+/// Older firmware stored the config without an [`Envelope`].
+/// Use [`Envelope::decode_compat`] to load such files: all known older layouts
+/// are upgraded to the current one, with the missing fields taken from
+/// [`Settings::default`].
+///
+/// Some of the settings are added "just in case" and might be not used yet
 /// or maybe even won't be ever used.
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -47,7 +52,7 @@ pub struct Settings {
     pub font_size: u8,
 
     /// Color scheme to use.
-    pub theme: u32,
+    pub theme: Theme,
 
     /// Automatically lock the screen after N minutes.
     ///
@@ -71,20 +76,156 @@ pub struct Settings {
     pub easter_eggs: bool,
 
     /// Any feature new fields will be encoded in this field, for backward compatibility.
+    ///
+    /// Use [`Settings::flag`] and [`Settings::set_flag`] to access the known flags
+    /// or [`Settings::extra_flags()`] to get all of them as [`ExtraFlags`].
+    /// Unknown bits are preserved as-is.
     pub extra_flags: u32,
 }
 
+impl Settings {
+    /// Get [`Settings::extra_flags`] as a set of flags.
+    #[must_use]
+    pub const fn extra_flags(&self) -> ExtraFlags {
+        ExtraFlags(self.extra_flags)
+    }
+
+    /// Replace [`Settings::extra_flags`] with the given set of flags.
+    pub const fn set_extra_flags(&mut self, flags: ExtraFlags) {
+        self.extra_flags = flags.0;
+    }
+
+    /// Check if the given flag from [`Settings::extra_flags`] is set.
+    #[must_use]
+    pub const fn flag(&self, flag: ExtraFlag) -> bool {
+        self.extra_flags().contains(flag)
+    }
+
+    /// Set or clear the given flag in [`Settings::extra_flags`].
+    ///
+    /// Other bits, including the ones unknown to this version of the crate,
+    /// are not affected.
+    pub const fn set_flag(&mut self, flag: ExtraFlag, value: bool) {
+        let mut flags = self.extra_flags();
+        flags.set(flag, value);
+        self.set_extra_flags(flags);
+    }
+}
+
+flag_enum! {
+    /// Boolean settings stored as bits of [`Settings::extra_flags`].
+    pub enum ExtraFlag: u32 {
+        /// Show FPS counter on top of the running app.
+        ShowFps = 0,
+        /// Show the battery level in the launcher.
+        ShowBattery = 1,
+        /// Use 24-hour time format instead of 12-hour.
+        Clock24h = 2,
+    }
+}
+
+flag_set! {
+    /// A set of [`ExtraFlag`], as stored in [`Settings::extra_flags`].
+    pub struct ExtraFlags(u32): ExtraFlag;
+}
+
 impl Encode<'_> for Settings {}
 
+impl Envelope<'_> for Settings {
+    const FILE: &'static str = "sys/config";
+    const MAGIC: [u8; 4] = *b"FFCF";
+    const VERSION: u8 = 1;
+
+    fn decode_version(version: u8, payload: &[u8]) -> Result<Self, EnvelopeError> {
+        match version {
+            0 => Ok(decode_legacy(payload)?),
+            Self::VERSION => Ok(postcard::from_bytes(payload)?),
+            _ => Err(EnvelopeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Decode a bare config file written by older firmware.
+///
+/// New fields were always added at the end, so the layouts differ
+/// only by the number of trailing fields. The newest layouts are tried first.
+fn decode_legacy(payload: &[u8]) -> Result<Settings, postcard::Error> {
+    let err = match from_bytes_exact::<Settings>(payload) {
+        Ok(settings) => return Ok(settings),
+        Err(err) => err,
+    };
+    if let Ok((base, contrast, easter_eggs)) = from_bytes_exact::<(SettingsV0, bool, bool)>(payload)
+    {
+        return Ok(Settings {
+            contrast,
+            easter_eggs,
+            ..base.into()
+        });
+    }
+    if let Ok((base, contrast)) = from_bytes_exact::<(SettingsV0, bool)>(payload) {
+        return Ok(Settings {
+            contrast,
+            ..base.into()
+        });
+    }
+    if let Ok(base) = from_bytes_exact::<SettingsV0>(payload) {
+        return Ok(base.into());
+    }
+    Err(err)
+}
+
+/// The oldest known layout of [`Settings`].
+///
+/// Later layouts added `contrast`, then `easter_eggs`, and then `extra_flags`.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize)]
+struct SettingsV0 {
+    xp: u32,
+    badges: u32,
+    lang: [u8; 2],
+    country: [u8; 2],
+    name: String,
+    timezone: String,
+    rotate_screen: bool,
+    screen_brightness: u8,
+    leds_brightness: u8,
+    speakers_volume: u8,
+    headphones_volume: u8,
+    font_size: u8,
+    theme: Theme,
+    auto_lock: u8,
+    reduce_flashing: bool,
+    telemetry: bool,
+    gamepad_mode: bool,
+}
+
+impl From<SettingsV0> for Settings {
+    fn from(v: SettingsV0) -> Self {
+        Self {
+            xp: v.xp,
+            badges: v.badges,
+            lang: v.lang,
+            country: v.country,
+            name: v.name,
+            timezone: v.timezone,
+            rotate_screen: v.rotate_screen,
+            screen_brightness: v.screen_brightness,
+            leds_brightness: v.leds_brightness,
+            speakers_volume: v.speakers_volume,
+            headphones_volume: v.headphones_volume,
+            font_size: v.font_size,
+            theme: v.theme,
+            auto_lock: v.auto_lock,
+            reduce_flashing: v.reduce_flashing,
+            telemetry: v.telemetry,
+            gamepad_mode: v.gamepad_mode,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
-        /// * Primary:      Black       (0).
-        /// * Secondary:    Light Gray  (D).
-        /// * Accent:       Green       (6).
-        /// * Background:   White       (C).
-        /// * Index:        default     (00).
-        const DEFAULT_THEME: u32 = 0x_0D_6C_00;
-
         Self {
             xp: 0,
             badges: 0,
@@ -98,7 +239,7 @@ impl Default for Settings {
             speakers_volume: 64,
             headphones_volume: 64,
             font_size: 9,
-            theme: DEFAULT_THEME,
+            theme: Theme::DEFAULT,
             auto_lock: 5,
             reduce_flashing: false,
             telemetry: false,
@@ -130,9 +271,12 @@ pub struct DeviceInfo {
     pub serial: u32,
 
     /// Main chip firmware version.
-    pub main_version: (u8, u8, u8),
+    ///
+    /// The runtime is a part of it. So, that's the version to compare
+    /// against [`Meta::min_runtime`][crate::Meta::min_runtime].
+    pub main_version: Version,
     /// IO chip firmware version.
-    pub io_version: (u8, u8, u8),
+    pub io_version: Version,
 
     /// Currently active partition on the main chip.
     pub main_partition: u8,
@@ -141,3 +285,112 @@ pub struct DeviceInfo {
 }
 
 impl Encode<'_> for DeviceInfo {}
+
+impl Envelope<'_> for DeviceInfo {
+    const FILE: &'static str = "sys/device";
+    const MAGIC: [u8; 4] = *b"FFDV";
+    const VERSION: u8 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy() -> SettingsV0 {
+        SettingsV0 {
+            xp: 120,
+            badges: 4,
+            lang: [b'n', b'l'],
+            country: [b'N', b'L'],
+            name: "my-device".to_string(),
+            timezone: "Europe/Amsterdam".to_string(),
+            rotate_screen: true,
+            screen_brightness: 100,
+            leds_brightness: 10,
+            speakers_volume: 20,
+            headphones_volume: 30,
+            font_size: 12,
+            theme: Theme::from(0x_0D_6C_00),
+            auto_lock: 0,
+            reduce_flashing: true,
+            telemetry: false,
+            gamepad_mode: true,
+        }
+    }
+
+    #[test]
+    fn test_decode_compat_current() {
+        let given = Settings {
+            easter_eggs: true,
+            extra_flags: 3,
+            ..Settings::default()
+        };
+        let raw = given.encode_vec().unwrap();
+        assert_eq!(Settings::decode_compat(&raw).unwrap(), given);
+        let raw = given.encode_envelope_vec().unwrap();
+        assert_eq!(Settings::decode_compat(&raw).unwrap(), given);
+    }
+
+    #[test]
+    fn test_decode_compat_legacy() {
+        let raw = postcard::to_allocvec(&legacy()).unwrap();
+        let actual = Settings::decode_compat(&raw).unwrap();
+        assert_eq!(actual.name, "my-device");
+        assert_eq!(actual.xp, 120);
+        assert!(actual.gamepad_mode);
+        assert!(!actual.contrast);
+        assert_eq!(actual.extra_flags, 0);
+
+        let raw = postcard::to_allocvec(&(legacy(), true)).unwrap();
+        let actual = Settings::decode_compat(&raw).unwrap();
+        assert!(actual.contrast);
+        assert!(!actual.easter_eggs);
+        assert_eq!(actual.font_size, 12);
+
+        let raw = postcard::to_allocvec(&(legacy(), false, true)).unwrap();
+        let actual = Settings::decode_compat(&raw).unwrap();
+        assert!(!actual.contrast);
+        assert!(actual.easter_eggs);
+        assert_eq!(actual.extra_flags, 0);
+    }
+
+    #[test]
+    fn test_extra_flags() {
+        let mut settings = Settings::default();
+        assert!(!settings.flag(ExtraFlag::ShowBattery));
+        settings.set_flag(ExtraFlag::ShowBattery, true);
+        assert!(settings.flag(ExtraFlag::ShowBattery));
+        assert!(!settings.flag(ExtraFlag::ShowFps));
+        assert_eq!(settings.extra_flags, 0b10);
+        settings.set_flag(ExtraFlag::ShowBattery, false);
+        assert_eq!(settings.extra_flags, 0);
+
+        let mut flags = ExtraFlags::NONE;
+        flags.insert(ExtraFlag::Clock24h);
+        settings.set_extra_flags(flags);
+        assert_eq!(settings.extra_flags, 0b100);
+        assert!(settings.flag(ExtraFlag::Clock24h));
+        assert_eq!(settings.extra_flags(), flags);
+    }
+
+    #[test]
+    fn test_extra_flags_unknown_preserved() {
+        let mut settings = Settings {
+            extra_flags: 1 << 31 | 1 << 20,
+            ..Settings::default()
+        };
+        settings.set_flag(ExtraFlag::ShowFps, true);
+        let raw = settings.encode_envelope_vec().unwrap();
+        let mut actual = Settings::decode_envelope(&raw).unwrap();
+        assert!(actual.flag(ExtraFlag::ShowFps));
+        actual.set_flag(ExtraFlag::ShowFps, false);
+        assert_eq!(actual.extra_flags, 1 << 31 | 1 << 20);
+    }
+
+    #[test]
+    fn test_decode_compat_invalid() {
+        let raw = postcard::to_allocvec(&(legacy(), true, true, 3u32, 1u8)).unwrap();
+        assert!(Settings::decode_compat(&raw).is_err());
+        assert!(Settings::decode_compat(&[]).is_err());
+    }
+}
@@ -1,4 +1,15 @@
+mod error_code;
+mod fragment;
+mod frame;
+mod transaction;
+
+pub use error_code::*;
+pub use fragment::*;
+pub use frame::*;
+pub use transaction::*;
+
 use crate::encode::Encode;
+use crate::{InputState, Version};
 use serde::{Deserialize, Serialize};
 
 /// Request that the main chip sends to the IO chip.
@@ -66,10 +77,14 @@ pub enum Request<'a> {
 }
 
 impl<'a> Encode<'a> for Request<'a> {}
+impl<'a> Framed<'a> for Request<'a> {}
 
 /// Response that the IO chip sends back to the main chip.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Response<'a> {
+    /// Failed to handle the request.
+    ///
+    /// Sent by older IO chip firmware. Newer firmware sends [`Response::Fail`].
     Error(&'a str),
 
     /// Confirmation for [`Request::NetStart`].
@@ -88,10 +103,7 @@ pub enum Response<'a> {
     NetSendStatus(SendStatus),
 
     /// Response for [`Request::ReadInput`].
-    ///
-    /// The first item is the touch coordinates on the pad (if any).
-    /// The second is serialized bitflags of pressed buttons.
-    Input(Option<(u16, u16)>, u8),
+    Input(InputState),
 
     /// List of SSIDs of up to 6 available wifi Access Points.
     ///
@@ -135,16 +147,23 @@ pub enum Response<'a> {
 
     /// Response for [`Request::FirmwareInfo`].
     FirmwareInfo {
-        version: (u8, u8, u8),
+        version: Version,
         partition: u8,
     },
     /// Response for [`Request::FlashWrite`].
     FlashWritten,
     /// Response for [`Request::PartitionSwitch`].
     PartitionSwitched,
+
+    /// Failed to handle the request.
+    ///
+    /// Replaces [`Response::Error`] for newer IO chip firmware.
+    /// The code describes the failure, the message (if any) gives more details.
+    Fail(ErrorCode, Option<&'a str>),
 }
 
 impl<'a> Encode<'a> for Response<'a> {}
+impl<'a> Framed<'a> for Response<'a> {}
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub enum SendStatus {
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+/// The group of failures an [`ErrorCode`] belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Net,
+    Wifi,
+    Tcp,
+    Flash,
+    Protocol,
+    /// The error code is not known to this version of the crate.
+    Unknown,
+}
+
+/// Machine-readable reason why the IO chip failed to handle a request.
+///
+/// Sent in [`Response::Fail`][super::Response::Fail].
+/// Serialized as `u16` where the high byte is the category
+/// and the low byte is the code within the category.
+/// Codes that aren't known to this version of the crate
+/// decode as [`ErrorCode::Unknown`], so that new codes can be added
+/// without breaking older main chip firmware.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(from = "u16", into = "u16")]
+pub enum ErrorCode {
+    /// Networking is not started. See [`Request::NetStart`][super::Request::NetStart].
+    NetNotStarted,
+    /// Networking is already started.
+    NetAlreadyStarted,
+    /// The peer with the given MAC address is not known.
+    NetUnknownPeer,
+    /// The outgoing messages queue is full.
+    NetQueueFull,
+
+    /// Not connected to any wifi access point.
+    WifiNotConnected,
+    /// The access point with the given SSID is not found.
+    WifiNotFound,
+    /// The access point rejected the password.
+    WifiAuthFailed,
+    /// Failed to scan the air for access points.
+    WifiScanFailed,
+
+    /// There is no open TCP connection.
+    TcpNotConnected,
+    /// There is already an open TCP connection.
+    TcpAlreadyConnected,
+    /// Failed to connect to the TCP server.
+    TcpConnectFailed,
+    /// The TCP send buffer is full.
+    TcpBufferFull,
+    /// The TCP connection was closed by the server.
+    TcpClosed,
+
+    /// The write address is outside of the partition.
+    FlashOutOfRange,
+    /// Failed to write into the flash.
+    FlashWriteFailed,
+    /// Failed to erase the flash.
+    FlashEraseFailed,
+    /// There is no partition with the given index.
+    FlashBadPartition,
+
+    /// The request frame is corrupted.
+    ProtocolBadFrame,
+    /// The request cannot be decoded.
+    ProtocolBadRequest,
+    /// The response doesn't fit into a frame.
+    ProtocolTooLarge,
+
+    /// The error code is not known to this version of the crate.
+    ///
+    /// Produced only by decoding unrecognized codes,
+    /// so a known code never compares equal to it.
+    Unknown(UnknownCode),
+}
+
+/// The raw value of an [`ErrorCode`] not known to this version of the crate.
+///
+/// Cannot be constructed outside of the crate, use [`ErrorCode::from`] instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownCode(u16);
+
+impl UnknownCode {
+    /// The raw error code as sent on the wire.
+    #[must_use]
+    pub const fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl ErrorCode {
+    /// The group of failures the error belongs to.
+    #[must_use]
+    pub const fn category(&self) -> ErrorCategory {
+        match u16_from_code(*self) >> 8 {
+            1 => ErrorCategory::Net,
+            2 => ErrorCategory::Wifi,
+            3 => ErrorCategory::Tcp,
+            4 => ErrorCategory::Flash,
+            5 => ErrorCategory::Protocol,
+            _ => ErrorCategory::Unknown,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::NetNotStarted => "networking is not started",
+            Self::NetAlreadyStarted => "networking is already started",
+            Self::NetUnknownPeer => "unknown peer",
+            Self::NetQueueFull => "outgoing queue is full",
+            Self::WifiNotConnected => "not connected to wifi",
+            Self::WifiNotFound => "access point not found",
+            Self::WifiAuthFailed => "wifi authentication failed",
+            Self::WifiScanFailed => "wifi scan failed",
+            Self::TcpNotConnected => "no open TCP connection",
+            Self::TcpAlreadyConnected => "TCP connection is already open",
+            Self::TcpConnectFailed => "failed to connect to TCP server",
+            Self::TcpBufferFull => "TCP buffer is full",
+            Self::TcpClosed => "TCP connection is closed by server",
+            Self::FlashOutOfRange => "flash write out of range",
+            Self::FlashWriteFailed => "flash write failed",
+            Self::FlashEraseFailed => "flash erase failed",
+            Self::FlashBadPartition => "unknown partition",
+            Self::ProtocolBadFrame => "corrupted frame",
+            Self::ProtocolBadRequest => "invalid request",
+            Self::ProtocolTooLarge => "response is too large",
+            Self::Unknown(_) => "unknown error",
+        }
+    }
+}
+
+const fn u16_from_code(value: ErrorCode) -> u16 {
+    match value {
+        ErrorCode::NetNotStarted => 0x0101,
+        ErrorCode::NetAlreadyStarted => 0x0102,
+        ErrorCode::NetUnknownPeer => 0x0103,
+        ErrorCode::NetQueueFull => 0x0104,
+        ErrorCode::WifiNotConnected => 0x0201,
+        ErrorCode::WifiNotFound => 0x0202,
+        ErrorCode::WifiAuthFailed => 0x0203,
+        ErrorCode::WifiScanFailed => 0x0204,
+        ErrorCode::TcpNotConnected => 0x0301,
+        ErrorCode::TcpAlreadyConnected => 0x0302,
+        ErrorCode::TcpConnectFailed => 0x0303,
+        ErrorCode::TcpBufferFull => 0x0304,
+        ErrorCode::TcpClosed => 0x0305,
+        ErrorCode::FlashOutOfRange => 0x0401,
+        ErrorCode::FlashWriteFailed => 0x0402,
+        ErrorCode::FlashEraseFailed => 0x0403,
+        ErrorCode::FlashBadPartition => 0x0404,
+        ErrorCode::ProtocolBadFrame => 0x0501,
+        ErrorCode::ProtocolBadRequest => 0x0502,
+        ErrorCode::ProtocolTooLarge => 0x0503,
+        ErrorCode::Unknown(code) => code.0,
+    }
+}
+
+impl From<u16> for ErrorCode {
+    fn from(value: u16) -> Self {
+        match value {
+            0x0101 => Self::NetNotStarted,
+            0x0102 => Self::NetAlreadyStarted,
+            0x0103 => Self::NetUnknownPeer,
+            0x0104 => Self::NetQueueFull,
+            0x0201 => Self::WifiNotConnected,
+            0x0202 => Self::WifiNotFound,
+            0x0203 => Self::WifiAuthFailed,
+            0x0204 => Self::WifiScanFailed,
+            0x0301 => Self::TcpNotConnected,
+            0x0302 => Self::TcpAlreadyConnected,
+            0x0303 => Self::TcpConnectFailed,
+            0x0304 => Self::TcpBufferFull,
+            0x0305 => Self::TcpClosed,
+            0x0401 => Self::FlashOutOfRange,
+            0x0402 => Self::FlashWriteFailed,
+            0x0403 => Self::FlashEraseFailed,
+            0x0404 => Self::FlashBadPartition,
+            0x0501 => Self::ProtocolBadFrame,
+            0x0502 => Self::ProtocolBadRequest,
+            0x0503 => Self::ProtocolTooLarge,
+            _ => Self::Unknown(UnknownCode(value)),
+        }
+    }
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(value: ErrorCode) -> Self {
+        u16_from_code(value)
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "unknown error {:#06x}", code.get()),
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::Encode;
+    use crate::spi::Response;
+
+    #[test]
+    fn test_roundtrip() {
+        for raw in 0..0x0600 {
+            let code = ErrorCode::from(raw);
+            assert_eq!(u16::from(code), raw);
+            if code.category() == ErrorCategory::Unknown {
+                assert!(matches!(code, ErrorCode::Unknown(_)));
+            }
+            if let ErrorCode::Unknown(unknown) = code {
+                assert_eq!(unknown.get(), raw);
+                assert_eq!(code.as_str(), "unknown error");
+            }
+        }
+        assert_eq!(ErrorCode::from(0x0101), ErrorCode::NetNotStarted);
+        assert_eq!(ErrorCode::TcpBufferFull.category(), ErrorCategory::Tcp);
+        let code = ErrorCode::from(0x0420);
+        assert_eq!(code.category(), ErrorCategory::Flash);
+        assert_eq!(code.to_string(), "unknown error 0x0420");
+    }
+
+    #[test]
+    fn test_response() {
+        // The legacy variant keeps its wire format.
+        let raw = Response::Error("x").encode_vec().unwrap();
+        assert_eq!(raw, [0, 1, b'x']);
+        assert_eq!(Response::decode(&raw).unwrap(), Response::Error("x"));
+
+        let resp = Response::Fail(ErrorCode::FlashOutOfRange, Some("0x1000"));
+        let raw = resp.encode_vec().unwrap();
+        assert_eq!(Response::decode(&raw).unwrap(), resp);
+    }
+}
@@ -0,0 +1,383 @@
+use super::{Framed, MAX_PAYLOAD};
+use crate::encode::Encode;
+use serde::{Deserialize, Serialize};
+
+/// The maximum size of the fragment header when encoded.
+///
+/// It's the message ID, the varint-encoded index and count,
+/// and the varint-encoded data length.
+pub const FRAGMENT_HEADER_SIZE: usize = 9;
+
+/// The maximum amount of data carried by a single fragment.
+///
+/// A fragment with that much data still fits into a single SPI frame.
+pub const MAX_FRAGMENT_DATA: usize = MAX_PAYLOAD - FRAGMENT_HEADER_SIZE;
+
+/// The maximum size of a payload that can be split into fragments.
+pub const MAX_FRAGMENTED_PAYLOAD: usize = MAX_FRAGMENT_DATA * u16::MAX as usize;
+
+/// Error returned when splitting a payload into fragments or reassembling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentError {
+    /// The payload is bigger than [`MAX_FRAGMENTED_PAYLOAD`].
+    TooManyFragments,
+    /// The fragment index is not less than the fragments count.
+    InvalidIndex,
+    /// The fragment with the given index is already received.
+    Duplicate(u16),
+    /// The fragment with the given index was expected but another one received.
+    Missing(u16),
+    /// A fragment of another message arrived before the current message was complete.
+    Interrupted,
+    /// The reassembled payload doesn't fit into the buffer.
+    BufferFull,
+}
+
+impl FragmentError {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooManyFragments => "payload is too big",
+            Self::InvalidIndex => "fragment index out of range",
+            Self::Duplicate(_) => "duplicate fragment",
+            Self::Missing(_) => "missing fragment",
+            Self::Interrupted => "message is interrupted",
+            Self::BufferFull => "buffer is full",
+        }
+    }
+}
+
+impl core::fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Duplicate(index) | Self::Missing(index) => {
+                write!(f, "{} #{index}", self.as_str())
+            }
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
+impl core::error::Error for FragmentError {}
+
+/// A numbered chunk of a payload that doesn't fit into a single SPI frame.
+///
+/// Each fragment is sent as its own frame (see [`Framed`]).
+/// Both sides must agree in advance that fragmentation is used.
+/// Usually, the payload is an encoded [`Request`][super::Request]
+/// or [`Response`][super::Response].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fragment<'a> {
+    /// The ID of the message the fragment belongs to.
+    ///
+    /// Should be different for every next message, so that fragments
+    /// of different messages are not mixed up.
+    pub id: u8,
+
+    /// The index of the fragment in the message, starting from 0.
+    pub index: u16,
+
+    /// The total number of fragments in the message. Never zero.
+    pub count: u16,
+
+    /// The chunk of the payload, up to [`MAX_FRAGMENT_DATA`] bytes.
+    pub data: &'a [u8],
+}
+
+impl Fragment<'_> {
+    /// The position of the fragment data in the payload.
+    ///
+    /// Can be used to write the data directly to its final location,
+    /// like when flashing a firmware image.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.index as usize * MAX_FRAGMENT_DATA
+    }
+
+    /// Check if it's the last fragment of the message.
+    #[must_use]
+    pub const fn is_last(&self) -> bool {
+        self.index + 1 == self.count
+    }
+}
+
+impl<'a> Encode<'a> for Fragment<'a> {}
+impl<'a> Framed<'a> for Fragment<'a> {}
+
+/// Split the payload into fragments that fit into a single SPI frame each.
+///
+/// An empty payload produces a single empty fragment.
+///
+/// # Errors
+///
+/// Returns [`FragmentError::TooManyFragments`] if the payload is bigger
+/// than [`MAX_FRAGMENTED_PAYLOAD`].
+pub fn fragments(id: u8, payload: &[u8]) -> Result<Fragments<'_>, FragmentError> {
+    let count = payload.len().div_ceil(MAX_FRAGMENT_DATA).max(1);
+    let Ok(count) = u16::try_from(count) else {
+        return Err(FragmentError::TooManyFragments);
+    };
+    Ok(Fragments {
+        id,
+        payload,
+        index: 0,
+        count,
+    })
+}
+
+/// Iterator over fragments of a payload. Created by [`fragments`].
+#[derive(Clone, Debug)]
+pub struct Fragments<'a> {
+    id: u8,
+    payload: &'a [u8],
+    index: u16,
+    count: u16,
+}
+
+impl<'a> Iterator for Fragments<'a> {
+    type Item = Fragment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let size = self.payload.len().min(MAX_FRAGMENT_DATA);
+        let (data, rest) = self.payload.split_at(size);
+        let fragment = Fragment {
+            id: self.id,
+            index: self.index,
+            count: self.count,
+            data,
+        };
+        self.payload = rest;
+        self.index += 1;
+        Some(fragment)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = usize::from(self.count - self.index);
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Fragments<'_> {}
+
+/// Collects fragments of a message into a fixed-size buffer of `N` bytes.
+///
+/// Fragments must arrive in order. Any gap or repetition is reported as an error.
+///
+/// The ID and the fragments count of the last completed message are remembered,
+/// so that a retransmitted fragment isn't delivered twice. The sender must use
+/// a different ID for the next message, or the receiver must call
+/// [`Reassembler::reset`] before it.
+#[derive(Clone, Debug)]
+pub struct Reassembler<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    id: u8,
+    next: u16,
+    count: u16,
+}
+
+impl<const N: usize> Reassembler<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            id: 0,
+            next: 0,
+            count: 0,
+        }
+    }
+
+    /// Check if some fragments of the current message are received but not all of them.
+    #[must_use]
+    pub const fn in_progress(&self) -> bool {
+        self.next < self.count
+    }
+
+    /// Drop the partially received message and forget the last completed one.
+    pub const fn reset(&mut self) {
+        self.len = 0;
+        self.next = 0;
+        self.count = 0;
+    }
+
+    /// Add the next fragment of the message.
+    ///
+    /// Returns the full payload when the last fragment is received
+    /// and `None` if more fragments are expected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FragmentError::Duplicate`] if the fragment is already received,
+    /// either as part of the message in progress or of the last completed one.
+    /// The fragment is ignored and the reassembly can continue.
+    ///
+    /// On any other error, the partially received message is dropped.
+    /// If the error is [`FragmentError::Interrupted`], the fragment
+    /// can be pushed again to start receiving the new message.
+    pub fn push(&mut self, fragment: &Fragment<'_>) -> Result<Option<&[u8]>, FragmentError> {
+        if fragment.index >= fragment.count {
+            self.reset();
+            return Err(FragmentError::InvalidIndex);
+        }
+        let same_message = fragment.id == self.id && fragment.count == self.count;
+        if same_message && fragment.index < self.next {
+            return Err(FragmentError::Duplicate(fragment.index));
+        }
+        if self.in_progress() {
+            if !same_message {
+                self.reset();
+                return Err(FragmentError::Interrupted);
+            }
+            if fragment.index != self.next {
+                let missing = self.next;
+                self.reset();
+                return Err(FragmentError::Missing(missing));
+            }
+        } else {
+            if fragment.index != 0 {
+                self.reset();
+                return Err(FragmentError::Missing(0));
+            }
+            self.len = 0;
+            self.id = fragment.id;
+            self.count = fragment.count;
+        }
+
+        let end = self.len + fragment.data.len();
+        let Some(dst) = self.buf.get_mut(self.len..end) else {
+            self.reset();
+            return Err(FragmentError::BufferFull);
+        };
+        dst.copy_from_slice(fragment.data);
+        self.len = end;
+        self.next += 1;
+        if self.in_progress() {
+            return Ok(None);
+        }
+        Ok(Some(&self.buf[..self.len]))
+    }
+}
+
+impl<const N: usize> Default for Reassembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::{MAX_FRAME, Request};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_roundtrip() {
+        let data: Vec<u8> = (0..1000u16).map(|i| i.to_le_bytes()[0]).collect();
+        let payload = Request::TcpSend(&data).encode_vec().unwrap();
+        let frags = fragments(7, &payload).unwrap();
+        assert_eq!(frags.len(), 5);
+
+        let mut reassembler = Reassembler::<1024>::new();
+        let mut buf = [0; MAX_FRAME];
+        let mut result = None;
+        for frag in frags {
+            let frame = frag.encode_frame(&mut buf).unwrap().to_vec();
+            let frag = Fragment::decode_frame(&frame).unwrap();
+            assert_eq!(frag.offset(), usize::from(frag.index) * MAX_FRAGMENT_DATA);
+            if let Some(payload) = reassembler.push(&frag).unwrap() {
+                assert!(frag.is_last());
+                result = Some(payload.to_vec());
+            }
+        }
+        let result = result.unwrap();
+        let Request::TcpSend(actual) = Request::decode(&result).unwrap() else {
+            panic!("wrong variant");
+        };
+        assert_eq!(actual, data);
+        assert!(!reassembler.in_progress());
+    }
+
+    #[test]
+    fn test_max_fragment_fits_frame() {
+        let data = [0; MAX_FRAGMENT_DATA];
+        let frag = Fragment {
+            id: u8::MAX,
+            index: u16::MAX - 1,
+            count: u16::MAX,
+            data: &data,
+        };
+        let mut buf = [0; MAX_FRAME];
+        assert!(frag.encode_frame(&mut buf).is_ok());
+    }
+
+    #[test]
+    fn test_empty_payload() {
+        let frags: Vec<_> = fragments(1, &[]).unwrap().collect();
+        assert_eq!(frags.len(), 1);
+        let mut reassembler = Reassembler::<0>::new();
+        assert_eq!(reassembler.push(&frags[0]), Ok(Some(&[][..])));
+    }
+
+    #[test]
+    fn test_same_id() {
+        let mut reassembler = Reassembler::<16>::new();
+        let first = fragments(0, b"one").unwrap().next().unwrap();
+        let second = fragments(0, b"two").unwrap().next().unwrap();
+        assert_eq!(reassembler.push(&first), Ok(Some(&b"one"[..])));
+        assert_eq!(reassembler.push(&first), Err(FragmentError::Duplicate(0)));
+        assert_eq!(reassembler.push(&second), Err(FragmentError::Duplicate(0)));
+        reassembler.reset();
+        assert_eq!(reassembler.push(&second), Ok(Some(&b"two"[..])));
+        let third = fragments(1, b"three").unwrap().next().unwrap();
+        assert_eq!(reassembler.push(&third), Ok(Some(&b"three"[..])));
+    }
+
+    #[test]
+    fn test_errors() {
+        let payload = [1; MAX_FRAGMENT_DATA * 3];
+        let frags: Vec<_> = fragments(1, &payload).unwrap().collect();
+        let mut reassembler = Reassembler::<{ MAX_FRAGMENT_DATA * 3 }>::new();
+
+        assert_eq!(reassembler.push(&frags[1]), Err(FragmentError::Missing(0)));
+        assert_eq!(reassembler.push(&frags[0]), Ok(None));
+        assert_eq!(
+            reassembler.push(&frags[0]),
+            Err(FragmentError::Duplicate(0))
+        );
+        assert!(reassembler.in_progress());
+        assert_eq!(reassembler.push(&frags[2]), Err(FragmentError::Missing(1)));
+        assert!(!reassembler.in_progress());
+
+        assert_eq!(reassembler.push(&frags[0]), Ok(None));
+        let other: Vec<_> = fragments(2, &payload).unwrap().collect();
+        assert_eq!(reassembler.push(&other[0]), Err(FragmentError::Interrupted));
+        assert_eq!(reassembler.push(&other[0]), Ok(None));
+        assert_eq!(reassembler.push(&other[1]), Ok(None));
+        assert!(reassembler.push(&other[2]).unwrap().is_some());
+        assert_eq!(
+            reassembler.push(&other[2]),
+            Err(FragmentError::Duplicate(2))
+        );
+
+        let mut small = Reassembler::<10>::new();
+        assert_eq!(small.push(&frags[0]), Err(FragmentError::BufferFull));
+
+        let bad = Fragment {
+            id: 0,
+            index: 2,
+            count: 2,
+            data: &[],
+        };
+        assert_eq!(small.push(&bad), Err(FragmentError::InvalidIndex));
+        assert!(fragments(0, &vec![0; MAX_FRAGMENTED_PAYLOAD]).is_ok());
+        assert_eq!(
+            fragments(0, &vec![0; MAX_FRAGMENTED_PAYLOAD + 1]).unwrap_err(),
+            FragmentError::TooManyFragments
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::encode::{Encode, from_bytes_exact};
+
+/// The maximum size of a frame payload.
+///
+/// The payload size is transferred as a single byte.
+pub const MAX_PAYLOAD: usize = 255;
+
+/// The number of bytes a frame adds to the payload: the length byte and CRC.
+pub const FRAME_OVERHEAD: usize = 3;
+
+/// The maximum size of a full frame, including the header and CRC.
+pub const MAX_FRAME: usize = MAX_PAYLOAD + FRAME_OVERHEAD;
+
+/// Error returned when encoding or decoding an SPI frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The payload doesn't fit into a single frame.
+    ///
+    /// The value is the size of the payload.
+    TooLarge(usize),
+    /// The buffer is too small to hold the frame.
+    BufferTooSmall,
+    /// The frame is shorter than its header says.
+    Truncated,
+    /// The frame is longer than its header says.
+    TrailingBytes,
+    /// The checksum doesn't match the content. The frame is corrupted.
+    BadCrc { expected: u16, actual: u16 },
+    /// The frame is valid but the payload cannot be encoded or decoded.
+    Postcard(postcard::Error),
+}
+
+impl From<postcard::Error> for FrameError {
+    fn from(value: postcard::Error) -> Self {
+        Self::Postcard(value)
+    }
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooLarge(size) => {
+                write!(f, "payload is {size} bytes, the limit is {MAX_PAYLOAD}")
+            }
+            Self::BufferTooSmall => write!(f, "buffer is too small"),
+            Self::Truncated => write!(f, "frame is truncated"),
+            Self::TrailingBytes => write!(f, "frame has trailing bytes"),
+            Self::BadCrc { expected, actual } => {
+                write!(f, "bad CRC: expected {expected:#06x}, got {actual:#06x}")
+            }
+            Self::Postcard(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Postcard(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Wrap the payload into a frame.
+///
+/// The frame is the payload size as a single byte, followed by the payload,
+/// followed by CRC-16/CCITT-FALSE (big-endian) of the size and the payload.
+///
+/// # Errors
+///
+/// Returns [`FrameError::TooLarge`] if the payload is longer than [`MAX_PAYLOAD`]
+/// and [`FrameError::BufferTooSmall`] if the frame doesn't fit into the buffer.
+pub fn encode_frame<'b>(payload: &[u8], buf: &'b mut [u8]) -> Result<&'b [u8], FrameError> {
+    let Ok(size) = u8::try_from(payload.len()) else {
+        return Err(FrameError::TooLarge(payload.len()));
+    };
+    let frame_size = payload.len() + FRAME_OVERHEAD;
+    let Some(frame) = buf.get_mut(..frame_size) else {
+        return Err(FrameError::BufferTooSmall);
+    };
+    frame[0] = size;
+    frame[1..=payload.len()].copy_from_slice(payload);
+    let crc = crc16(&frame[..=payload.len()]);
+    frame[payload.len() + 1..].copy_from_slice(&crc.to_be_bytes());
+    Ok(frame)
+}
+
+/// Check the frame integrity and extract the payload from it.
+///
+/// # Errors
+///
+/// Returns [`FrameError::Truncated`] or [`FrameError::TrailingBytes`]
+/// if the frame size doesn't match the header and [`FrameError::BadCrc`]
+/// if the frame is corrupted.
+pub fn decode_frame(frame: &[u8]) -> Result<&[u8], FrameError> {
+    let Some(&size) = frame.first() else {
+        return Err(FrameError::Truncated);
+    };
+    let body_size = usize::from(size) + 1;
+    let expected_size = body_size + 2;
+    if frame.len() < expected_size {
+        return Err(FrameError::Truncated);
+    }
+    if frame.len() > expected_size {
+        return Err(FrameError::TrailingBytes);
+    }
+    let (body, crc) = frame.split_at(body_size);
+    let expected = u16::from_be_bytes([crc[0], crc[1]]);
+    let actual = crc16(body);
+    if expected != actual {
+        return Err(FrameError::BadCrc { expected, actual });
+    }
+    Ok(&body[1..])
+}
+
+/// Calculate the full frame size from the first byte of the frame.
+///
+/// Useful to know how many more bytes to read from SPI
+/// after the header byte is received.
+#[must_use]
+pub const fn frame_size(header: u8) -> usize {
+    header as usize + FRAME_OVERHEAD
+}
+
+/// CRC-16/CCITT-FALSE checksum used by SPI frames.
+#[must_use]
+pub const fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= (data[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x1021
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// SPI message that can be sent as a single frame.
+///
+/// Implemented for [`Request`][super::Request] and [`Response`][super::Response].
+pub trait Framed<'a>: Encode<'a> {
+    /// Encode the message and wrap it into a frame.
+    ///
+    /// The buffer of [`MAX_FRAME`] bytes is always enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FrameError::TooLarge`] if the encoded message
+    /// is longer than [`MAX_PAYLOAD`].
+    fn encode_frame<'b>(&self, buf: &'b mut [u8]) -> Result<&'b [u8], FrameError> {
+        let size = self.size();
+        let Ok(header) = u8::try_from(size) else {
+            return Err(FrameError::TooLarge(size));
+        };
+        let Some(frame) = buf.get_mut(..size + FRAME_OVERHEAD) else {
+            return Err(FrameError::BufferTooSmall);
+        };
+        frame[0] = header;
+        self.encode_buf(&mut frame[1..=size])?;
+        let crc = crc16(&frame[..=size]);
+        frame[size + 1..].copy_from_slice(&crc.to_be_bytes());
+        Ok(frame)
+    }
+
+    /// Check the frame integrity and decode the message from it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`decode_frame`]. Additionally, returns [`FrameError::Postcard`]
+    /// if the payload is not a valid message.
+    fn decode_frame(frame: &'a [u8]) -> Result<Self, FrameError> {
+        let payload = decode_frame(frame)?;
+        Ok(from_bytes_exact(payload)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi::{Request, Response};
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+        assert_eq!(crc16(b""), 0xffff);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut buf = [0; MAX_FRAME];
+        let req = Request::TcpSend(b"hello");
+        let frame = req.encode_frame(&mut buf).unwrap();
+        assert_eq!(frame.len(), frame_size(frame[0]));
+        let frame = frame.to_vec();
+        let Request::TcpSend(data) = Request::decode_frame(&frame).unwrap() else {
+            panic!("wrong variant");
+        };
+        assert_eq!(data, b"hello");
+
+        let frame = encode_frame(&[1, 2, 3], &mut buf).unwrap();
+        assert_eq!(decode_frame(frame).unwrap(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut buf = [0; 512];
+        let data = [0; MAX_PAYLOAD];
+        let req = Request::TcpSend(&data);
+        assert_eq!(
+            req.encode_frame(&mut buf),
+            Err(FrameError::TooLarge(MAX_PAYLOAD + 3))
+        );
+        assert_eq!(
+            encode_frame(&[0; 256], &mut buf),
+            Err(FrameError::TooLarge(256))
+        );
+        assert!(encode_frame(&data, &mut buf).is_ok());
+        assert_eq!(
+            encode_frame(&data, &mut buf[..100]),
+            Err(FrameError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let mut buf = [0; MAX_FRAME];
+        let frame = Response::TcpChunk(b"data").encode_frame(&mut buf).unwrap();
+        let mut frame = frame.to_vec();
+        assert_eq!(
+            Response::decode_frame(&frame[..frame.len() - 1]),
+            Err(FrameError::Truncated)
+        );
+        assert_eq!(decode_frame(&[]), Err(FrameError::Truncated));
+        frame.push(0);
+        assert_eq!(
+            Response::decode_frame(&frame),
+            Err(FrameError::TrailingBytes)
+        );
+        frame.pop();
+        frame[3] ^= 0x10;
+        assert!(matches!(
+            Response::decode_frame(&frame),
+            Err(FrameError::BadCrc { .. })
+        ));
+    }
+}
@@ -0,0 +1,355 @@
+use super::{ErrorCode, Request, Response, SendStatus};
+use crate::{InputState, Version};
+
+/// The variant of [`Response`] without the payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseKind {
+    Error,
+    NetStarted,
+    NetStopped,
+    NetLocalAddr,
+    NetAdvertised,
+    NetIncoming,
+    NetNoIncoming,
+    NetSent,
+    NetSendStatus,
+    Input,
+    WifiScan,
+    WifiStatus,
+    WifiConnected,
+    WifiDisconnected,
+    TcpConnected,
+    TcpStatus,
+    TcpSent,
+    TcpChunk,
+    TcpClosed,
+    FirmwareInfo,
+    FlashWritten,
+    PartitionSwitched,
+    Fail,
+}
+
+impl Response<'_> {
+    /// The variant of the response.
+    #[must_use]
+    pub const fn kind(&self) -> ResponseKind {
+        match self {
+            Self::Error(_) => ResponseKind::Error,
+            Self::NetStarted => ResponseKind::NetStarted,
+            Self::NetStopped => ResponseKind::NetStopped,
+            Self::NetLocalAddr(_) => ResponseKind::NetLocalAddr,
+            Self::NetAdvertised => ResponseKind::NetAdvertised,
+            Self::NetIncoming(_, _) => ResponseKind::NetIncoming,
+            Self::NetNoIncoming => ResponseKind::NetNoIncoming,
+            Self::NetSent => ResponseKind::NetSent,
+            Self::NetSendStatus(_) => ResponseKind::NetSendStatus,
+            Self::Input(_) => ResponseKind::Input,
+            Self::WifiScan(_) => ResponseKind::WifiScan,
+            Self::WifiStatus(_) => ResponseKind::WifiStatus,
+            Self::WifiConnected => ResponseKind::WifiConnected,
+            Self::WifiDisconnected => ResponseKind::WifiDisconnected,
+            Self::TcpConnected => ResponseKind::TcpConnected,
+            Self::TcpStatus(_) => ResponseKind::TcpStatus,
+            Self::TcpSent => ResponseKind::TcpSent,
+            Self::TcpChunk(_) => ResponseKind::TcpChunk,
+            Self::TcpClosed => ResponseKind::TcpClosed,
+            Self::FirmwareInfo { .. } => ResponseKind::FirmwareInfo,
+            Self::FlashWritten => ResponseKind::FlashWritten,
+            Self::PartitionSwitched => ResponseKind::PartitionSwitched,
+            Self::Fail(_, _) => ResponseKind::Fail,
+        }
+    }
+}
+
+impl Request<'_> {
+    /// The response variants that are valid replies to the request.
+    ///
+    /// [`Response::Error`] and [`Response::Fail`] are valid replies
+    /// to any request and aren't included.
+    #[must_use]
+    pub const fn expected(&self) -> &'static [ResponseKind] {
+        use ResponseKind as K;
+        match self {
+            Self::NetStart => &[K::NetStarted],
+            Self::NetStop => &[K::NetStopped],
+            Self::NetLocalAddr => &[K::NetLocalAddr],
+            Self::NetAdvertise => &[K::NetAdvertised],
+            Self::NetRecv => &[K::NetIncoming, K::NetNoIncoming],
+            Self::NetSend(_, _) => &[K::NetSent],
+            Self::NetSendStatus(_) => &[K::NetSendStatus],
+            Self::ReadInput => &[K::Input],
+            Self::WifiScan => &[K::WifiScan],
+            Self::WifiConnect(_, _) => &[K::WifiConnected],
+            Self::WifiStatus => &[K::WifiStatus],
+            Self::WifiDisconnect => &[K::WifiDisconnected],
+            Self::TcpConnect(_, _) => &[K::TcpConnected],
+            Self::TcpStatus => &[K::TcpStatus],
+            Self::TcpSend(_) => &[K::TcpSent],
+            Self::TcpRecv => &[K::TcpChunk],
+            Self::TcpClose => &[K::TcpClosed],
+            Self::FirmwareInfo => &[K::FirmwareInfo],
+            Self::FlashWrite(_, _) => &[K::FlashWritten],
+            Self::PartitionSwitch(_) => &[K::PartitionSwitched],
+        }
+    }
+
+    /// Check that the response is a valid reply to the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError::Remote`] if the IO chip replied with [`Response::Error`],
+    /// [`ProtocolError::Failed`] if it replied with [`Response::Fail`],
+    /// and [`ProtocolError::Unexpected`] if the response is of a wrong variant.
+    ///
+    /// For confirmations (like [`Response::NetStarted`]), that's all the checking needed.
+    /// To get the payload of other responses, use typed accessors
+    /// like [`Response::into_input`] instead.
+    pub fn check<'r>(&self, response: Response<'r>) -> Result<Response<'r>, ProtocolError<'r>> {
+        let expected = self.expected();
+        if !expected.contains(&response.kind()) {
+            return Err(response.unexpected(expected));
+        }
+        Ok(response)
+    }
+}
+
+impl<'a> Response<'a> {
+    /// Get the address from [`Response::NetLocalAddr`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_net_local_addr(self) -> Result<[u8; 6], ProtocolError<'a>> {
+        match self {
+            Self::NetLocalAddr(addr) => Ok(addr),
+            _ => Err(self.unexpected(&[ResponseKind::NetLocalAddr])),
+        }
+    }
+
+    /// Get the message from [`Response::NetIncoming`] or `None` for [`Response::NetNoIncoming`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    #[allow(clippy::type_complexity)]
+    pub const fn into_net_incoming(self) -> Result<Option<([u8; 6], &'a [u8])>, ProtocolError<'a>> {
+        match self {
+            Self::NetIncoming(addr, data) => Ok(Some((addr, data))),
+            Self::NetNoIncoming => Ok(None),
+            _ => Err(self.unexpected(&[ResponseKind::NetIncoming, ResponseKind::NetNoIncoming])),
+        }
+    }
+
+    /// Get the status from [`Response::NetSendStatus`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_net_send_status(self) -> Result<SendStatus, ProtocolError<'a>> {
+        match self {
+            Self::NetSendStatus(status) => Ok(status),
+            _ => Err(self.unexpected(&[ResponseKind::NetSendStatus])),
+        }
+    }
+
+    /// Get the input from [`Response::Input`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_input(self) -> Result<InputState, ProtocolError<'a>> {
+        match self {
+            Self::Input(input) => Ok(input),
+            _ => Err(self.unexpected(&[ResponseKind::Input])),
+        }
+    }
+
+    /// Get the SSIDs from [`Response::WifiScan`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_wifi_scan(self) -> Result<[&'a str; 6], ProtocolError<'a>> {
+        match self {
+            Self::WifiScan(ssids) => Ok(ssids),
+            _ => Err(self.unexpected(&[ResponseKind::WifiScan])),
+        }
+    }
+
+    /// Get the status from [`Response::WifiStatus`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_wifi_status(self) -> Result<u8, ProtocolError<'a>> {
+        match self {
+            Self::WifiStatus(status) => Ok(status),
+            _ => Err(self.unexpected(&[ResponseKind::WifiStatus])),
+        }
+    }
+
+    /// Get the status from [`Response::TcpStatus`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_tcp_status(self) -> Result<u8, ProtocolError<'a>> {
+        match self {
+            Self::TcpStatus(status) => Ok(status),
+            _ => Err(self.unexpected(&[ResponseKind::TcpStatus])),
+        }
+    }
+
+    /// Get the bytes from [`Response::TcpChunk`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_tcp_chunk(self) -> Result<&'a [u8], ProtocolError<'a>> {
+        match self {
+            Self::TcpChunk(data) => Ok(data),
+            _ => Err(self.unexpected(&[ResponseKind::TcpChunk])),
+        }
+    }
+
+    /// Get the version and the partition from [`Response::FirmwareInfo`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProtocolError`] if the response is an error or of another variant.
+    pub const fn into_firmware_info(self) -> Result<(Version, u8), ProtocolError<'a>> {
+        match self {
+            Self::FirmwareInfo { version, partition } => Ok((version, partition)),
+            _ => Err(self.unexpected(&[ResponseKind::FirmwareInfo])),
+        }
+    }
+
+    /// Convert the response that didn't match the expectations into an error.
+    const fn unexpected(self, expected: &'static [ResponseKind]) -> ProtocolError<'a> {
+        match self {
+            Self::Error(message) => ProtocolError::Remote(message),
+            Self::Fail(code, message) => ProtocolError::Failed(code, message),
+            _ => ProtocolError::Unexpected {
+                expected,
+                actual: self.kind(),
+            },
+        }
+    }
+}
+
+/// Error returned by [`Request::check`] and typed accessors of [`Response`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtocolError<'a> {
+    /// The IO chip failed to handle the request. Sent by older IO chip firmware.
+    Remote(&'a str),
+    /// The IO chip failed to handle the request with the given error code.
+    Failed(ErrorCode, Option<&'a str>),
+    /// The response doesn't match the request.
+    Unexpected {
+        expected: &'static [ResponseKind],
+        actual: ResponseKind,
+    },
+}
+
+impl ProtocolError<'_> {
+    /// The error code sent by the IO chip, if any.
+    #[must_use]
+    pub const fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Failed(code, _) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for ProtocolError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Remote(message) => write!(f, "IO chip error: {message}"),
+            Self::Failed(code, None) => write!(f, "IO chip error: {code}"),
+            Self::Failed(code, Some(message)) => write!(f, "IO chip error: {code}: {message}"),
+            Self::Unexpected { expected, actual } => {
+                write!(f, "unexpected response {actual:?}, expected {expected:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ProtocolError<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let req = Request::NetRecv;
+        assert_eq!(
+            req.check(Response::NetNoIncoming),
+            Ok(Response::NetNoIncoming)
+        );
+        let resp = Response::NetIncoming([1; 6], b"hi");
+        assert_eq!(req.check(resp.clone()), Ok(resp));
+        assert_eq!(
+            req.check(Response::NetSent),
+            Err(ProtocolError::Unexpected {
+                expected: &[ResponseKind::NetIncoming, ResponseKind::NetNoIncoming],
+                actual: ResponseKind::NetSent,
+            })
+        );
+        assert_eq!(
+            Request::TcpClose.check(Response::Error("oops")),
+            Err(ProtocolError::Remote("oops"))
+        );
+        let err = Request::TcpSend(b"")
+            .check(Response::Fail(ErrorCode::TcpBufferFull, None))
+            .unwrap_err();
+        assert_eq!(err, ProtocolError::Failed(ErrorCode::TcpBufferFull, None));
+        assert_eq!(err.code(), Some(ErrorCode::TcpBufferFull));
+    }
+
+    #[test]
+    fn test_typed() {
+        let input = InputState::default();
+        assert_eq!(Response::Input(input).into_input(), Ok(input));
+        assert_eq!(
+            Response::TcpSent.into_input(),
+            Err(ProtocolError::Unexpected {
+                expected: &[ResponseKind::Input],
+                actual: ResponseKind::TcpSent,
+            })
+        );
+        assert_eq!(
+            Response::Error("oops").into_tcp_chunk(),
+            Err(ProtocolError::Remote("oops"))
+        );
+        let version = Version::new(1, 2, 3);
+        let resp = Response::FirmwareInfo {
+            version,
+            partition: 1,
+        };
+        assert_eq!(resp.into_firmware_info(), Ok((version, 1)));
+        assert_eq!(Response::NetNoIncoming.into_net_incoming(), Ok(None));
+        let resp = Response::Fail(ErrorCode::NetNotStarted, None);
+        assert_eq!(
+            resp.into_net_incoming(),
+            Err(ProtocolError::Failed(ErrorCode::NetNotStarted, None))
+        );
+    }
+
+    #[test]
+    fn test_expected_is_never_empty() {
+        let requests = [
+            Request::NetStart,
+            Request::NetSend([0; 6], b""),
+            Request::WifiConnect("ssid", "pass"),
+            Request::FlashWrite(0, b""),
+            Request::PartitionSwitch(1),
+        ];
+        for req in requests {
+            assert!(!req.expected().is_empty());
+            assert!(!req.expected().contains(&ResponseKind::Error));
+            assert!(!req.expected().contains(&ResponseKind::Fail));
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use crate::encode::Encode;
+use crate::encode::{Encode, Envelope, EnvelopeError, from_bytes_exact};
+use crate::error::collect;
+use crate::{Badge, Badges, Board, Boards, Date, Error, ScoreError};
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 /// Player-specific app stats, like playtime.
@@ -28,23 +31,17 @@ pub struct Stats {
     pub launches: [u32; 4],
 
     /// The date when the app was installed.
-    ///
-    /// The date is a tuple of year, month, and day of month.
-    pub installed_on: (u16, u8, u8),
+    pub installed_on: Date,
 
     /// The date when the app was updated.
-    ///
-    /// The date is a tuple of year, month, and day of month.
-    pub updated_on: (u16, u8, u8),
+    pub updated_on: Date,
 
     /// The date when the app was launched last time.
-    ///
-    /// The date is a tuple of year, month, and day of month.
-    pub launched_on: (u16, u8, u8),
+    pub launched_on: Date,
 
     /// How much XP the player has earned in the game.
     ///
-    /// Cannot be more than 1000.
+    /// Cannot be more than [`Stats::MAX_XP`].
     pub xp: u16,
 
     /// The progress of earning each badge.
@@ -57,8 +54,204 @@ pub struct Stats {
     pub scores: Box<[BoardScores]>,
 }
 
+impl Stats {
+    /// The maximum XP that the player can earn in a single app.
+    pub const MAX_XP: u16 = 1000;
+
+    /// Add the given number of steps to the progress of the badge with the given index.
+    ///
+    /// If the badge is earned by this call, the badge XP is added to [`Stats::xp`]
+    /// (but not above [`Stats::MAX_XP`]) and true is returned.
+    /// Returns `None` if there is no badge with the given index.
+    pub fn advance_badge(&mut self, index: usize, badge: &Badge<'_>, steps: u16) -> Option<bool> {
+        let progress = self.badges.get_mut(index)?;
+        let earned = progress.advance(steps);
+        if earned {
+            self.xp = self
+                .xp
+                .saturating_add(u16::from(badge.xp))
+                .min(Self::MAX_XP);
+        }
+        Some(earned)
+    }
+
+    /// Check that all the dates are valid.
+    ///
+    /// Dates aren't validated when decoding, see [`Date`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DateField`] for every invalid date,
+    /// with the name of the field.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let dates = [
+            ("installed_on", self.installed_on),
+            ("updated_on", self.updated_on),
+            ("launched_on", self.launched_on),
+        ];
+        for (field, date) in dates {
+            if let Err(error) = date.validate() {
+                errors.push(error.for_field(field));
+            }
+        }
+        collect(errors)
+    }
+
+    /// Adjust the stats to match the (possibly changed) badges and boards of the app.
+    ///
+    /// Call it after the app is updated. Badges and boards are matched by index,
+    /// so the progress is preserved for the ones that still exist.
+    /// New entries start from scratch, removed ones are dropped.
+    /// The badge goals are updated to the new [`crate::Badge::steps`]
+    /// and [`Stats::xp`] is recalculated based on the earned badges.
+    /// If the number of steps is unknown (zero), the old goal is kept.
+    /// New badges with unknown steps get the goal of 1.
+    #[must_use]
+    pub fn reconcile(self, badges: &Badges<'_>, boards: &Boards<'_>) -> Self {
+        let mut old_badges = self.badges.into_iter();
+        let new_badges: Box<[BadgeProgress]> = badges
+            .badges
+            .iter()
+            .map(|badge| {
+                let Some(old) = old_badges.next() else {
+                    return BadgeProgress {
+                        new: false,
+                        done: 0,
+                        goal: badge.steps.max(1),
+                    };
+                };
+                let goal = if badge.steps == 0 {
+                    old.goal
+                } else {
+                    badge.steps
+                };
+                let mut progress = BadgeProgress {
+                    new: old.new,
+                    done: old.done,
+                    goal,
+                };
+                if progress.earned() && !old.earned() {
+                    progress.new = true;
+                }
+                progress
+            })
+            .collect();
+
+        let mut old_scores = self.scores.into_iter();
+        let new_scores: Box<[BoardScores]> = boards
+            .boards
+            .iter()
+            .map(|_| old_scores.next().unwrap_or_default())
+            .collect();
+
+        let mut xp: u16 = 0;
+        for (badge, progress) in badges.badges.iter().zip(&new_badges) {
+            if progress.earned() {
+                xp = xp.saturating_add(u16::from(badge.xp));
+            }
+        }
+
+        Self {
+            xp: xp.min(Self::MAX_XP),
+            badges: new_badges,
+            scores: new_scores,
+            ..self
+        }
+    }
+
+    /// The number of days passed since the app was installed.
+    #[must_use]
+    pub const fn days_since_install(&self, today: Date) -> i32 {
+        today.days_since(self.installed_on)
+    }
+
+    /// The number of days passed since the app was launched last time.
+    #[must_use]
+    pub const fn days_since_launch(&self, today: Date) -> i32 {
+        today.days_since(self.launched_on)
+    }
+
+    /// Record the app launch with the given number of players.
+    ///
+    /// Call [`Stats::end_session`] when the app exits.
+    pub const fn start_session(&mut self, players: u8, today: Date) {
+        let idx = players_index(players);
+        self.launches[idx] = self.launches[idx].saturating_add(1);
+        self.launched_on = today;
+    }
+
+    /// Record the play time of a finished session with the given number of players.
+    ///
+    /// Plays shorter than 1 minute are not recorded.
+    pub const fn end_session(&mut self, players: u8, minutes: u32) {
+        if minutes == 0 {
+            return;
+        }
+        let idx = players_index(players);
+        self.minutes[idx] = self.minutes[idx].saturating_add(minutes);
+        if minutes > self.longest_play[idx] {
+            self.longest_play[idx] = minutes;
+        }
+    }
+
+    /// Record a full play session: both the launch and the play time.
+    ///
+    /// A shortcut for [`Stats::start_session`] followed by [`Stats::end_session`].
+    pub const fn record_session(&mut self, players: u8, minutes: u32, today: Date) {
+        self.start_session(players, today);
+        self.end_session(players, minutes);
+    }
+}
+
+/// The index in [`Stats::minutes`] and similar arrays for the given player count.
+///
+/// Zero players is treated as single-player.
+const fn players_index(players: u8) -> usize {
+    match players {
+        0 | 1 => 0,
+        2 => 1,
+        3 => 2,
+        _ => 3,
+    }
+}
+
 impl Encode<'_> for Stats {}
 
+impl Envelope<'_> for Stats {
+    const FILE: &'static str = "stats";
+    const MAGIC: [u8; 4] = *b"FFST";
+    const VERSION: u8 = 2;
+
+    fn decode_version(version: u8, payload: &[u8]) -> Result<Self, EnvelopeError> {
+        match version {
+            // Version 1 had 16-bit scores. They are encoded as varints,
+            // so the layout is compatible but empty slots need to be updated.
+            // A bare file may also be in the current layout. Then the update
+            // affects only scores of exactly `i16::MIN`.
+            0 | 1 => {
+                let mut stats: Self = from_bytes_exact(payload)?;
+                let old_empty = i32::from(i16::MIN);
+                for scores in &mut stats.scores {
+                    for score in scores.me.iter_mut() {
+                        if *score == old_empty {
+                            *score = BoardScores::EMPTY;
+                        }
+                    }
+                    for friend in scores.friends.iter_mut() {
+                        if friend.score == old_empty {
+                            friend.score = BoardScores::EMPTY;
+                        }
+                    }
+                }
+                Ok(stats)
+            }
+            Self::VERSION => Ok(postcard::from_bytes(payload)?),
+            _ => Err(EnvelopeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct BadgeProgress {
     /// If true, the earning of the badge hasn't been shown to the player yet.
@@ -78,26 +271,145 @@ impl BadgeProgress {
     pub const fn earned(&self) -> bool {
         self.done >= self.goal
     }
+
+    /// Add the given number of steps to the progress.
+    ///
+    /// Returns true if the badge has just been earned by this call.
+    /// In that case, the badge is also marked as [`BadgeProgress::new`].
+    pub const fn advance(&mut self, steps: u16) -> bool {
+        let was_earned = self.earned();
+        self.done = self.done.saturating_add(steps);
+        let just_earned = !was_earned && self.earned();
+        if just_earned {
+            self.new = true;
+        }
+        just_earned
+    }
+
+    /// Mark the earned badge as already shown to the player.
+    pub const fn mark_shown(&mut self) {
+        self.new = false;
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct BoardScores {
     /// Top scores of the local player.
-    pub me: Box<[i16; 8]>,
+    ///
+    /// Empty slots are filled with [`BoardScores::EMPTY`].
+    pub me: Box<[i32; 8]>,
 
     /// Top scores of friends.
     pub friends: Box<[FriendScore; 8]>,
 }
 
+impl BoardScores {
+    /// The score value used for empty slots.
+    ///
+    /// It's never a valid score, see [`Board::check_score`].
+    pub const EMPTY: i32 = i32::MIN;
+
+    /// Add a new score of the local player.
+    ///
+    /// The scores are sorted from the best to the worst and the higher score
+    /// is always the better one (see [`Board::min`]). If the score is equal to an existing
+    /// one, it's placed below it. Returns the index the score was placed at
+    /// (0 is the top) or `None` if the score is not good enough to make it to the list.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScoreError`] if the score is outside of the board's `min..=max` range.
+    pub fn insert(&mut self, board: &Board<'_>, score: i32) -> Result<Option<usize>, ScoreError> {
+        board.check_score(score)?;
+        Ok(insert_sorted(self.me.as_mut_slice(), score, |s| *s))
+    }
+
+    /// Add a new score of a friend.
+    ///
+    /// A friend is listed at most once, with their best score.
+    /// If the friend is already listed with a better (or the same) score,
+    /// nothing changes and `None` is returned. Otherwise, works the same
+    /// as [`BoardScores::insert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScoreError`] if the score is outside of the board's `min..=max` range.
+    pub fn insert_friend(
+        &mut self,
+        board: &Board<'_>,
+        friend: FriendScore,
+    ) -> Result<Option<usize>, ScoreError> {
+        board.check_score(friend.score)?;
+        let friends = self.friends.as_mut_slice();
+        let existing = friends
+            .iter()
+            .position(|f| f.score != Self::EMPTY && f.index == friend.index);
+        if let Some(pos) = existing {
+            if friends[pos].score >= friend.score {
+                return Ok(None);
+            }
+            friends[pos..].rotate_left(1);
+            if let Some(last) = friends.last_mut() {
+                last.score = Self::EMPTY;
+            }
+        }
+        Ok(insert_sorted(friends, friend, |f| f.score))
+    }
+}
+
+/// Insert the item into the list sorted by the score in descending order.
+///
+/// The last item is pushed out of the list.
+fn insert_sorted<T: Copy>(list: &mut [T], item: T, score: impl Fn(&T) -> i32) -> Option<usize> {
+    let new_score = score(&item);
+    let pos = list.iter().position(|old| score(old) < new_score)?;
+    list[pos..].rotate_right(1);
+    list[pos] = item;
+    Some(pos)
+}
+
+impl Default for BoardScores {
+    fn default() -> Self {
+        let friend = FriendScore {
+            index: 0,
+            score: Self::EMPTY,
+        };
+        Self {
+            me: Box::new([Self::EMPTY; 8]),
+            friends: Box::new([friend; 8]),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
 pub struct FriendScore {
     pub index: u16,
-    pub score: i16,
+    pub score: i32,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DateError;
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    /// Stats of a freshly installed app, for tests to override only what they need.
+    fn stats() -> Stats {
+        Stats {
+            minutes: [0; 4],
+            longest_play: [0; 4],
+            launches: [0; 4],
+            installed_on: date(2023, 12, 31),
+            updated_on: date(2023, 12, 31),
+            launched_on: date(2023, 12, 31),
+            xp: 0,
+            badges: Box::new([]),
+            scores: Box::new([]),
+        }
+    }
 
     #[test]
     fn test_roundtrip() {
@@ -105,16 +417,372 @@ mod tests {
             minutes: [11, 12, 13, 14],
             longest_play: [21, 22, 23, 24],
             launches: [31, 32, 33, 34],
-            installed_on: (2023, 12, 31),
-            updated_on: (2024, 1, 17),
-            launched_on: (2024, 2, 28),
+            updated_on: date(2024, 1, 17),
+            launched_on: date(2024, 2, 28),
             xp: 32,
-            badges: Box::new([]),
-            scores: Box::new([]),
+            ..stats()
         };
         let mut buf = vec![0; given.size()];
         let raw = given.encode_buf(&mut buf).unwrap();
         let actual = Stats::decode(raw).unwrap();
         assert_eq!(given, actual);
     }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let given = Stats {
+            minutes: [11, 12, 13, 14],
+            launched_on: date(2024, 2, 28),
+            xp: 32,
+            ..stats()
+        };
+        let raw = given.encode_envelope_vec().unwrap();
+        assert_eq!(&raw[..4], b"FFST");
+        let actual = Stats::decode_envelope(&raw).unwrap();
+        assert_eq!(given, actual);
+
+        let mut buf = vec![0; given.envelope_size()];
+        let raw2 = given.encode_envelope_buf(&mut buf).unwrap();
+        assert_eq!(raw, raw2);
+    }
+
+    #[test]
+    fn test_envelope_errors() {
+        let mut raw = stats().encode_envelope_vec().unwrap();
+        let err = Stats::decode_envelope(&raw[..3]).unwrap_err();
+        assert_eq!(
+            err,
+            Error::Envelope {
+                file: "stats",
+                error: EnvelopeError::TooShort
+            }
+        );
+        let err = crate::Settings::decode_envelope(&raw).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Envelope {
+                file: "sys/config",
+                error: EnvelopeError::WrongKind { .. }
+            }
+        ));
+        raw[4] = 99;
+        let err = Stats::decode_envelope(&raw).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid stats: unsupported schema version: 99"
+        );
+    }
+
+    #[test]
+    fn test_decode_compat_magic_prefix() {
+        // A bare file that happens to start with the magic bytes.
+        let given = Stats {
+            minutes: [70, 70, 83, 84],
+            ..stats()
+        };
+        let raw = given.encode_vec().unwrap();
+        assert_eq!(&raw[..4], b"FFST");
+        assert!(Stats::decode_envelope(&raw).is_err());
+        let actual = Stats::decode_compat(&raw).unwrap();
+        assert_eq!(given, actual);
+    }
+
+    #[test]
+    fn test_record_session() {
+        let mut stats = stats();
+        stats.record_session(1, 10, date(2024, 1, 2));
+        stats.record_session(1, 4, date(2024, 1, 3));
+        stats.record_session(2, 0, date(2024, 1, 4));
+        stats.record_session(7, 30, date(2024, 1, 5));
+        assert_eq!(stats.launches, [2, 1, 0, 1]);
+        assert_eq!(stats.minutes, [14, 0, 0, 30]);
+        assert_eq!(stats.longest_play, [10, 0, 0, 30]);
+        assert_eq!(stats.launched_on, date(2024, 1, 5));
+        assert_eq!(stats.days_since_launch(date(2024, 1, 15)), 10);
+        assert_eq!(stats.days_since_install(date(2024, 1, 15)), 15);
+
+        stats.end_session(4, u32::MAX);
+        assert_eq!(stats.minutes[3], u32::MAX);
+        assert_eq!(stats.longest_play[3], u32::MAX);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        use crate::{Badge, Board};
+        use alloc::borrow::Cow;
+
+        let badge = |xp, steps| Badge {
+            position: 0,
+            xp,
+            steps,
+            hidden: 0,
+            name: "badge",
+            descr: "",
+        };
+        let board = Board {
+            position: 0,
+            min: i32::MIN,
+            max: i32::MAX,
+            time: false,
+            decimals: 0,
+            name: "board",
+        };
+        let mut scores = BoardScores::default();
+        scores.me[0] = 42;
+        let stats = Stats {
+            minutes: [5, 0, 0, 0],
+            xp: 10,
+            badges: Box::new([
+                BadgeProgress {
+                    new: false,
+                    done: 1,
+                    goal: 1,
+                },
+                BadgeProgress {
+                    new: false,
+                    done: 3,
+                    goal: 5,
+                },
+                BadgeProgress {
+                    new: false,
+                    done: 1,
+                    goal: 1,
+                },
+            ]),
+            scores: Box::new([scores.clone(), BoardScores::default()]),
+            ..stats()
+        };
+        let badges = Badges::new(Cow::Owned(alloc::vec![
+            badge(10, 1),
+            badge(20, 3),
+            badge(30, 2)
+        ]));
+        let boards = Boards::new(Cow::Owned(alloc::vec![board]));
+        let actual = stats.reconcile(&badges, &boards);
+        assert_eq!(
+            actual.badges.as_ref(),
+            &[
+                BadgeProgress {
+                    new: false,
+                    done: 1,
+                    goal: 1
+                },
+                BadgeProgress {
+                    new: true,
+                    done: 3,
+                    goal: 3
+                },
+                BadgeProgress {
+                    new: false,
+                    done: 1,
+                    goal: 2
+                },
+            ]
+        );
+        assert_eq!(actual.scores.as_ref(), &[scores]);
+        assert_eq!(actual.xp, 30);
+        assert_eq!(actual.minutes, [5, 0, 0, 0]);
+
+        let badges = Badges::new(Cow::Owned(alloc::vec![badge(10, 1); 4]));
+        let actual = actual.reconcile(&badges, &Boards::new(Cow::Owned(alloc::vec![])));
+        assert_eq!(actual.badges.len(), 4);
+        assert_eq!(actual.badges[3].done, 0);
+        assert!(actual.scores.is_empty());
+        assert_eq!(actual.xp, 30);
+    }
+
+    #[test]
+    fn test_reconcile_legacy_badges() {
+        // A `_badges` file written before `Badge::steps` was added.
+        let old = [(0u16, 50u8, 1u16, "Collector", "")];
+        let raw = postcard::to_allocvec(&old.as_slice()).unwrap();
+        let badges = Badges::decode_compat(&raw).unwrap();
+        let stats = Stats {
+            badges: Box::new([BadgeProgress {
+                new: false,
+                done: 3,
+                goal: 10,
+            }]),
+            ..stats()
+        };
+        let boards = Boards::new(alloc::borrow::Cow::Owned(alloc::vec![]));
+        let actual = stats.reconcile(&badges, &boards);
+        assert_eq!(
+            actual.badges.as_ref(),
+            &[BadgeProgress {
+                new: false,
+                done: 3,
+                goal: 10,
+            }]
+        );
+        assert_eq!(actual.xp, 0);
+    }
+
+    #[test]
+    fn test_insert_score() {
+        let board = Board {
+            position: 0,
+            min: -100,
+            max: 100,
+            time: false,
+            decimals: 0,
+            name: "board",
+        };
+        let mut scores = BoardScores::default();
+        assert_eq!(scores.insert(&board, 10), Ok(Some(0)));
+        assert_eq!(scores.insert(&board, 20), Ok(Some(0)));
+        assert_eq!(scores.insert(&board, -5), Ok(Some(2)));
+        assert_eq!(scores.insert(&board, 10), Ok(Some(2)));
+        assert_eq!(scores.insert(&board, 101), Err(ScoreError::AboveMax));
+        assert_eq!(scores.insert(&board, -101), Err(ScoreError::BelowMin));
+        let unbounded = Board {
+            min: i32::MIN,
+            ..board.clone()
+        };
+        assert_eq!(
+            scores.insert(&unbounded, BoardScores::EMPTY),
+            Err(ScoreError::BelowMin)
+        );
+        assert_eq!(scores.insert(&unbounded, i32::MIN + 1), Ok(Some(4)));
+        scores.me[4] = BoardScores::EMPTY;
+        let e = BoardScores::EMPTY;
+        assert_eq!(*scores.me, [20, 10, 10, -5, e, e, e, e]);
+        for _ in 0..4 {
+            scores.insert(&board, 50).unwrap();
+        }
+        assert_eq!(*scores.me, [50, 50, 50, 50, 20, 10, 10, -5]);
+        assert_eq!(scores.insert(&board, -10), Ok(None));
+        assert_eq!(scores.insert(&board, 0), Ok(Some(7)));
+        assert_eq!(*scores.me, [50, 50, 50, 50, 20, 10, 10, 0]);
+    }
+
+    #[test]
+    fn test_insert_friend_score() {
+        let board = Board {
+            position: 0,
+            min: i32::MIN,
+            max: 0,
+            time: true,
+            decimals: 0,
+            name: "board",
+        };
+        let friend = |index, score| FriendScore { index, score };
+        let mut scores = BoardScores::default();
+        assert_eq!(scores.insert_friend(&board, friend(1, -30)), Ok(Some(0)));
+        assert_eq!(scores.insert_friend(&board, friend(2, -20)), Ok(Some(0)));
+        assert_eq!(scores.insert_friend(&board, friend(1, -40)), Ok(None));
+        assert_eq!(scores.insert_friend(&board, friend(1, -10)), Ok(Some(0)));
+        assert_eq!(
+            scores.insert_friend(&board, friend(3, 5)),
+            Err(ScoreError::AboveMax)
+        );
+        assert_eq!(scores.friends[0], friend(1, -10));
+        assert_eq!(scores.friends[1], friend(2, -20));
+        assert_eq!(scores.friends[2].score, BoardScores::EMPTY);
+    }
+
+    #[test]
+    fn test_decode_v1_scores() {
+        #[derive(Serialize)]
+        struct BoardScoresV1 {
+            me: [i16; 8],
+            friends: [(u16, i16); 8],
+        }
+
+        let e = i16::MIN;
+        let old = BoardScoresV1 {
+            me: [300, -2, e, e, e, e, e, e],
+            friends: [
+                (3, 100),
+                (0, e),
+                (0, e),
+                (0, e),
+                (0, e),
+                (0, e),
+                (0, e),
+                (0, e),
+            ],
+        };
+        let mut raw = postcard::to_allocvec(&(
+            [0u32; 4],
+            [0u32; 4],
+            [0u32; 4],
+            (2023u16, 12u8, 31u8),
+            (2023u16, 12u8, 31u8),
+            (2023u16, 12u8, 31u8),
+            0u16,
+            &[(); 0][..],
+            &[old][..],
+        ))
+        .unwrap();
+        raw.splice(0..0, *b"FFST\x01");
+        let stats = Stats::decode_envelope(&raw).unwrap();
+        let e = BoardScores::EMPTY;
+        assert_eq!(*stats.scores[0].me, [300, -2, e, e, e, e, e, e]);
+        assert_eq!(
+            stats.scores[0].friends[0],
+            FriendScore {
+                index: 3,
+                score: 100
+            }
+        );
+        assert_eq!(stats.scores[0].friends[1].score, e);
+    }
+
+    #[test]
+    fn test_decode_zero_date() {
+        let raw = postcard::to_allocvec(&(
+            [3u32, 0, 0, 0],
+            [3u32, 0, 0, 0],
+            [1u32, 0, 0, 0],
+            (2023u16, 12u8, 31u8),
+            (2023u16, 12u8, 31u8),
+            (0u16, 0u8, 0u8),
+            0u16,
+            &[(); 0][..],
+            &[(); 0][..],
+        ))
+        .unwrap();
+        let stats = Stats::decode_compat(&raw).unwrap();
+        assert_eq!(stats.minutes[0], 3);
+        assert_eq!(
+            stats.validate(),
+            Err(vec![DateError::InvalidMonth.for_field("launched_on")])
+        );
+    }
+
+    #[test]
+    fn test_advance_badge() {
+        let badge = Badge {
+            position: 0,
+            xp: 150,
+            steps: 3,
+            hidden: 2,
+            name: "badge",
+            descr: "",
+        };
+        let progress = BadgeProgress {
+            new: false,
+            done: 0,
+            goal: 3,
+        };
+        let mut stats = Stats {
+            xp: 900,
+            badges: Box::new([progress]),
+            ..stats()
+        };
+        assert!(!badge.is_visible(&stats.badges[0]));
+        assert_eq!(stats.advance_badge(0, &badge, 2), Some(false));
+        assert!(badge.is_visible(&stats.badges[0]));
+        assert!(!stats.badges[0].new);
+        assert_eq!(stats.xp, 900);
+        assert_eq!(stats.advance_badge(0, &badge, 2), Some(true));
+        assert!(stats.badges[0].new);
+        assert_eq!(stats.xp, Stats::MAX_XP);
+        stats.badges[0].mark_shown();
+        assert_eq!(stats.advance_badge(0, &badge, 1), Some(false));
+        assert!(!stats.badges[0].new);
+        assert_eq!(stats.badges[0].done, 5);
+        assert_eq!(stats.advance_badge(1, &badge, 1), None);
+    }
 }
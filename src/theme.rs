@@ -0,0 +1,200 @@
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// The highest palette index. The default palette has 16 colors.
+const MAX_COLOR: u8 = 0xF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeValidationError {
+    PrimaryOutOfRange,
+    SecondaryOutOfRange,
+    AccentOutOfRange,
+    BgOutOfRange,
+}
+
+impl ThemeValidationError {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::PrimaryOutOfRange => "primary color must be a palette index from 0 to 15",
+            Self::SecondaryOutOfRange => "secondary color must be a palette index from 0 to 15",
+            Self::AccentOutOfRange => "accent color must be a palette index from 0 to 15",
+            Self::BgOutOfRange => "background color must be a palette index from 0 to 15",
+        }
+    }
+}
+
+impl Display for ThemeValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl core::error::Error for ThemeValidationError {}
+
+/// Color scheme.
+///
+/// Each color is an index in the color palette.
+/// On the wire, it's packed into a single `u32`:
+///
+/// * Bits 24-31: [`Theme::extra`].
+/// * Bits 20-23: [`Theme::primary`].
+/// * Bits 16-19: [`Theme::secondary`].
+/// * Bits 12-15: [`Theme::accent`].
+/// * Bits 8-11: [`Theme::bg`].
+/// * Bits 0-7: [`Theme::id`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(from = "u32", into = "u32")]
+pub struct Theme {
+    /// The main color of text and UI elements.
+    pub primary: u8,
+
+    /// The color of secondary text and inactive UI elements.
+    pub secondary: u8,
+
+    /// The color of highlighted and active UI elements.
+    pub accent: u8,
+
+    /// The background color.
+    pub bg: u8,
+
+    /// The index of a predefined theme. Zero for the default or a custom theme.
+    pub id: u8,
+
+    /// Not used yet. Preserved as-is when converting from and into `u32`.
+    pub extra: u8,
+}
+
+impl Theme {
+    /// The default color scheme.
+    ///
+    /// * Primary:      Black       (0).
+    /// * Secondary:    Light Gray  (D).
+    /// * Accent:       Green       (6).
+    /// * Background:   White       (C).
+    /// * Index:        default     (00).
+    pub const DEFAULT: Self = Self::from_u32(0x_0D_6C_00);
+
+    /// Create a custom theme from the given palette indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThemeValidationError`] if any of the colors is out of the palette range.
+    pub const fn new(
+        primary: u8,
+        secondary: u8,
+        accent: u8,
+        bg: u8,
+    ) -> Result<Self, ThemeValidationError> {
+        let theme = Self {
+            primary,
+            secondary,
+            accent,
+            bg,
+            id: 0,
+            extra: 0,
+        };
+        match theme.validate() {
+            Ok(()) => Ok(theme),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Validate that all colors are valid palette indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThemeValidationError`] if any of the colors is out of the palette range.
+    pub const fn validate(&self) -> Result<(), ThemeValidationError> {
+        if self.primary > MAX_COLOR {
+            return Err(ThemeValidationError::PrimaryOutOfRange);
+        }
+        if self.secondary > MAX_COLOR {
+            return Err(ThemeValidationError::SecondaryOutOfRange);
+        }
+        if self.accent > MAX_COLOR {
+            return Err(ThemeValidationError::AccentOutOfRange);
+        }
+        if self.bg > MAX_COLOR {
+            return Err(ThemeValidationError::BgOutOfRange);
+        }
+        Ok(())
+    }
+
+    const fn from_u32(v: u32) -> Self {
+        let [extra, colors1, colors2, id] = v.to_be_bytes();
+        Self {
+            primary: colors1 >> 4,
+            secondary: colors1 & 0xF,
+            accent: colors2 >> 4,
+            bg: colors2 & 0xF,
+            id,
+            extra,
+        }
+    }
+
+    /// Pack the theme into `u32`.
+    ///
+    /// Colors out of the palette range are truncated.
+    /// Use [`Theme::validate`] first to avoid it.
+    #[must_use]
+    pub const fn to_u32(self) -> u32 {
+        let colors1 = (self.primary & 0xF) << 4 | (self.secondary & 0xF);
+        let colors2 = (self.accent & 0xF) << 4 | (self.bg & 0xF);
+        u32::from_be_bytes([self.extra, colors1, colors2, self.id])
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<u32> for Theme {
+    fn from(value: u32) -> Self {
+        Self::from_u32(value)
+    }
+}
+
+impl From<Theme> for u32 {
+    fn from(value: Theme) -> Self {
+        value.to_u32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let theme = Theme::DEFAULT;
+        assert_eq!(theme.primary, 0x0);
+        assert_eq!(theme.secondary, 0xD);
+        assert_eq!(theme.accent, 0x6);
+        assert_eq!(theme.bg, 0xC);
+        assert_eq!(theme.id, 0);
+        assert_eq!(u32::from(theme), 0x_0D_6C_00);
+    }
+
+    #[test]
+    fn test_u32_roundtrip() {
+        for raw in [0, 0x_0D_6C_00, 0x_12_34_56_78, u32::MAX] {
+            assert_eq!(u32::from(Theme::from(raw)), raw);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Theme::new(1, 2, 3, 15).is_ok());
+        assert_eq!(
+            Theme::new(16, 2, 3, 4),
+            Err(ThemeValidationError::PrimaryOutOfRange)
+        );
+        assert_eq!(
+            Theme::new(1, 2, 3, 200),
+            Err(ThemeValidationError::BgOutOfRange)
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use crate::Error;
 use core::fmt::Display;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValidationError {
     TrailingMinus,
     DoubleMinus,
@@ -9,6 +11,11 @@ pub enum ValidationError {
     TooLong,
     TrailingSpace,
     TrailingDot,
+    Reserved,
+    NoDot,
+    OutOfRange,
+    LessThanMin,
+    Duplicate,
 }
 
 impl ValidationError {
@@ -29,8 +36,21 @@ impl ValidationError {
             Self::TooLong => "too long",
             Self::TrailingSpace => "must not start or end with space",
             Self::TrailingDot => "must not start or end with dot",
+            Self::Reserved => "is reserved",
+            Self::NoDot => "must contain a dot",
+            Self::OutOfRange => "out of range",
+            Self::LessThanMin => "must not be less than the minimum",
+            Self::Duplicate => "must be unique",
         }
     }
+
+    /// Attach the name of the invalid field to the error.
+    ///
+    /// Useful for `?`: `validate_id(id).map_err(|e| e.for_field("app_id"))?`.
+    #[must_use]
+    pub const fn for_field(self, field: &'static str) -> Error {
+        Error::Field { field, error: self }
+    }
 }
 
 impl Display for ValidationError {
@@ -50,10 +70,23 @@ impl Display for ValidationError {
             Self::TooLong => write!(f, "too long"),
             Self::TrailingSpace => write!(f, "must not start or end with space"),
             Self::TrailingDot => write!(f, "must not start or end with dot"),
+            Self::Reserved => write!(f, "is reserved"),
+            Self::NoDot => write!(f, "must contain a dot"),
+            Self::OutOfRange => write!(f, "out of range"),
+            Self::LessThanMin => write!(f, "must not be less than the minimum"),
+            Self::Duplicate => write!(f, "must be unique"),
         }
     }
 }
 
+impl core::error::Error for ValidationError {}
+
+/// Author IDs that cannot be used by third-party apps.
+///
+/// The `sys` author is used for system apps bundled with the firmware,
+/// like the launcher.
+pub const RESERVED_AUTHOR_IDS: &[&str] = &["sys"];
+
 /// Validate the author or the app ID or the device name.
 ///
 /// The ID should have at least one character and may contain only
@@ -0,0 +1,45 @@
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Semantic version of a firmware or runtime.
+///
+/// Versions are compared as `(major, minor, patch)` tuples.
+/// Encoded the same way as such a tuple.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl Version {
+    #[must_use]
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl From<(u8, u8, u8)> for Version {
+    fn from((major, minor, patch): (u8, u8, u8)) -> Self {
+        Self::new(major, minor, patch)
+    }
+}
+
+impl From<Version> for (u8, u8, u8) {
+    fn from(value: Version) -> Self {
+        (value.major, value.minor, value.patch)
+    }
+}
+
+/// Formats the version as `MAJOR.MINOR.PATCH`.
+impl Display for Version {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}